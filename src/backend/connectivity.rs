@@ -0,0 +1,64 @@
+use std::str::FromStr;
+
+use crate::error::Error;
+
+/// Which of the surrounding cells a search may step to from a given cell.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Connectivity {
+    /// Only up, down, left and right.
+    Four,
+    /// All eight surrounding cells, diagonals included.
+    #[default]
+    Eight,
+    /// Diagonals, except squeezing between two walls that touch at a corner.
+    EightNoCornerCut,
+    /// Diagonals only when both orthogonal cells next to the step are free.
+    EightStrict,
+}
+
+const ORTHOGONAL: [(i32, i32); 4] = [(1, 0), (0, -1), (0, 1), (-1, 0)];
+
+const DIAGONAL: [(i32, i32); 4] = [(1, -1), (1, 1), (-1, -1), (-1, 1)];
+
+impl Connectivity {
+    pub fn allows_diagonal(&self) -> bool {
+        !matches!(self, Connectivity::Four)
+    }
+
+    /// The `(dy, dx)` steps to try from a cell, orthogonal ones first.
+    pub fn offsets(&self) -> Vec<(i32, i32)> {
+        let mut offsets = ORTHOGONAL.to_vec();
+        if self.allows_diagonal() {
+            offsets.extend_from_slice(&DIAGONAL);
+        }
+        offsets
+    }
+
+    /// Whether a diagonal step may be taken given whether the two orthogonal
+    /// cells it passes between are walls.
+    pub fn allows_corner(&self, first_wall: bool, second_wall: bool) -> bool {
+        match self {
+            Connectivity::Four => false,
+            Connectivity::Eight => true,
+            Connectivity::EightNoCornerCut => !(first_wall && second_wall),
+            Connectivity::EightStrict => !first_wall && !second_wall,
+        }
+    }
+}
+
+impl FromStr for Connectivity {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "4" => Ok(Connectivity::Four),
+            "8" => Ok(Connectivity::Eight),
+            "8-no-corner-cut" => Ok(Connectivity::EightNoCornerCut),
+            "8-strict" => Ok(Connectivity::EightStrict),
+            _ => Err(Error::InvalidOptions(format!(
+                "unknown connectivity '{}'",
+                name
+            ))),
+        }
+    }
+}
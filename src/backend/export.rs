@@ -0,0 +1,188 @@
+use std::fmt::Write;
+
+use crate::{
+    backend::DataHandle,
+    cell::cell::{Cell, Position},
+    djikstra::{SearchResult, SearchStats},
+};
+
+/// Header of `DataHandle::summary_csv`.
+pub const SUMMARY_HEADER: &str = "maze,found,start_x,start_y,end_x,end_y,start_image_x,\
+start_image_y,end_image_x,end_image_y,path_length,path_cost,euclidean_length,nodes_expanded,\
+nodes_generated,heap_peak,reopenings,elapsed_ms";
+
+/// Header of `DataHandle::path_csv`.
+pub const PATH_HEADER: &str = "step,x,y,image_x,image_y";
+
+impl<T: Cell> DataHandle<T> {
+    /// The solution as a JSON object. Every point is given twice, as
+    /// `[x, y]`: under `matrix` in the coordinates the search used, and
+    /// under `image` in pixels of the source image. `cost`, `path` and
+    /// `waypoints` are `null` when there are none, and so is `reopenings`
+    /// for searches that never reopen nodes.
+    pub fn to_json(&self, result: &SearchResult, start: &Position, end: &Position) -> String {
+        let mut json = String::from("{\n");
+        let _ = writeln!(json, "  \"maze\": {},", quote(&self.name));
+        let _ = writeln!(json, "  \"found\": {},", result.path.is_some());
+        let _ = writeln!(json, "  \"start\": {},", self.point_json(start));
+        let _ = writeln!(json, "  \"end\": {},", self.point_json(end));
+        let _ = writeln!(
+            json,
+            "  \"cost\": {},",
+            path_cost(result).unwrap_or_else(|| String::from("null"))
+        );
+        let _ = writeln!(json, "  \"stats\": {},", stats_json(&result.stats));
+        let _ = writeln!(
+            json,
+            "  \"waypoints\": {},",
+            self.points_json(result.waypoints.as_deref())
+        );
+        let _ = writeln!(
+            json,
+            "  \"path\": {}",
+            self.points_json(result.path.as_deref())
+        );
+        json.push_str("}\n");
+        json
+    }
+
+    /// One CSV row per cell of the path, in order, with its matrix and
+    /// source image coordinates. Only the header when there is no path.
+    pub fn path_csv(&self, result: &SearchResult) -> String {
+        let mut csv = format!("{}\n", PATH_HEADER);
+        for (step, position) in result.path.iter().flatten().enumerate() {
+            let (image_x, image_y) = self.to_image(position);
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{}",
+                step,
+                position.x(),
+                position.y(),
+                image_x,
+                image_y
+            );
+        }
+        csv
+    }
+
+    /// A single CSV row with the ends, the cost and the statistics of the
+    /// search, under `SUMMARY_HEADER`. `path_cost` is left empty when there
+    /// is no path, and `reopenings` for searches that never reopen nodes.
+    pub fn summary_csv(&self, result: &SearchResult, start: &Position, end: &Position) -> String {
+        let (start_x, start_y) = self.to_image(start);
+        let (end_x, end_y) = self.to_image(end);
+        let stats = &result.stats;
+        format!(
+            "{}\n{},{},{},{},{},{},{},{},{},{},{},{},{:.3},{},{},{},{},{:.3}\n",
+            SUMMARY_HEADER,
+            csv_field(&self.name),
+            result.path.is_some(),
+            start.x(),
+            start.y(),
+            end.x(),
+            end.y(),
+            start_x,
+            start_y,
+            end_x,
+            end_y,
+            stats.path_length,
+            path_cost(result).unwrap_or_default(),
+            stats.euclidean_length,
+            stats.nodes_expanded,
+            stats.nodes_generated,
+            stats.heap_peak,
+            stats
+                .reopenings
+                .map(|count| count.to_string())
+                .unwrap_or_default(),
+            stats.elapsed.as_secs_f64() * 1000.0
+        )
+    }
+
+    fn point_json(&self, position: &Position) -> String {
+        let (image_x, image_y) = self.to_image(position);
+        format!(
+            "{{ \"matrix\": [{}, {}], \"image\": [{}, {}] }}",
+            position.x(),
+            position.y(),
+            image_x,
+            image_y
+        )
+    }
+
+    fn points_json(&self, points: Option<&[Position]>) -> String {
+        let points = match points {
+            Some(points) => points,
+            None => return String::from("null"),
+        };
+        let matrix: Vec<String> = points
+            .iter()
+            .map(|position| format!("[{}, {}]", position.x(), position.y()))
+            .collect();
+        let image: Vec<String> = points
+            .iter()
+            .map(|position| {
+                let (image_x, image_y) = self.to_image(position);
+                format!("[{}, {}]", image_x, image_y)
+            })
+            .collect();
+        format!(
+            "{{\n    \"matrix\": [{}],\n    \"image\": [{}]\n  }}",
+            matrix.join(", "),
+            image.join(", ")
+        )
+    }
+}
+
+/// The cost of the path, if there is one.
+fn path_cost(result: &SearchResult) -> Option<String> {
+    result
+        .path
+        .as_ref()
+        .map(|_| result.stats.path_cost.to_string())
+}
+
+fn stats_json(stats: &SearchStats) -> String {
+    format!(
+        "{{ \"path_length\": {}, \"path_cost\": {}, \"euclidean_length\": {:.3}, \
+         \"nodes_expanded\": {}, \"nodes_generated\": {}, \"heap_peak\": {}, \
+         \"reopenings\": {}, \"elapsed_ms\": {:.3} }}",
+        stats.path_length,
+        stats.path_cost,
+        stats.euclidean_length,
+        stats.nodes_expanded,
+        stats.nodes_generated,
+        stats.heap_peak,
+        stats
+            .reopenings
+            .map_or_else(|| String::from("null"), |count| count.to_string()),
+        stats.elapsed.as_secs_f64() * 1000.0
+    )
+}
+
+/// `text` as a JSON string.
+fn quote(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for character in text.chars() {
+        match character {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            character if character.is_control() => {
+                let _ = write!(quoted, "\\u{:04x}", character as u32);
+            }
+            character => quoted.push(character),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// `text` as a CSV field, quoted only when it has to be.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        String::from(text)
+    }
+}
@@ -1,9 +1,17 @@
 use crate::backend::matrix::Matrix;
 use crate::cell::cell::Cell;
-use image::{io::Reader as ImageReader, DynamicImage, ImageError, RgbImage};
+use crate::error::{Error, Result};
+use image::{
+    buffer::ConvertBuffer,
+    codecs::gif::{GifEncoder, Repeat},
+    io::Reader as ImageReader,
+    Delay, DynamicImage, Frame, RgbImage,
+};
 use std::{
     fs::{self, File, ReadDir},
-    io::{self, ErrorKind, Write},
+    io::{BufWriter, Write},
+    path::Path,
+    time::Duration,
 };
 
 #[derive(Clone)]
@@ -17,35 +25,72 @@ impl FileHandler {
         FileHandler { results, images }
     }
 
-    pub fn write_image(&self, image: &RgbImage, file_name: &str) {
-        if let Ok(_resp) = image.save(format!("{}{}.png", self.results, file_name)) {
-            println!("File saved");
-        };
+    pub fn write_image(&self, image: &RgbImage, file_name: &str) -> Result<()> {
+        fs::create_dir_all(&self.results)?;
+        image.save(format!("{}{}.png", self.results, file_name))?;
+        Ok(())
     }
 
-    pub fn read_image(&self, file_name: &str) -> Result<DynamicImage, ImageError> {
-        if let Ok(value) = ImageReader::open(format!("{}{}.jpg", self.images, file_name)) {
-            return value.decode();
-        };
-        Err(ImageError::IoError(io::Error::new(
-            ErrorKind::InvalidInput,
-            "Well shit",
-        )))
+    pub fn read_image(&self, file_name: &str) -> Result<DynamicImage> {
+        read_image_path(Path::new(&format!("{}{}.jpg", self.images, file_name)))
     }
 
     pub fn write_matrix<T: Cell>(&self, matrix: &Matrix<T>) {
         if let Ok(mut writer) = File::create(format!("{}test", self.results)) {
             for y in 0..matrix.y_size() {
                 for x in 0..matrix.x_size() {
-                    writer.write(format!("{}", &matrix[(y, x)]).as_ref());
-                    writer.write(" ".as_ref());
+                    let _ = write!(writer, "{} ", &matrix[(y, x)]);
                 }
-                writer.write("\n".as_ref());
+                let _ = writeln!(writer);
             }
         }
     }
 
-    pub fn read_directory(&self) -> io::Result<ReadDir> {
-        fs::read_dir(&self.images)
+    pub fn read_directory(&self) -> Result<ReadDir> {
+        Ok(fs::read_dir(&self.images)?)
+    }
+}
+
+pub fn read_image_path(path: &Path) -> Result<DynamicImage> {
+    if !path.is_file() {
+        return Err(Error::FileNotFound(path.display().to_string()));
+    }
+    Ok(ImageReader::open(path)?.with_guessed_format()?.decode()?)
+}
+
+/// Writes `frames` as a GIF that loops forever, showing each for `delay`.
+pub fn save_gif(
+    path: &Path,
+    frames: impl IntoIterator<Item = RgbImage>,
+    delay: Duration,
+) -> Result<()> {
+    let mut writer = GifWriter::create(path, delay)?;
+    for frame in frames {
+        writer.write(frame)?;
+    }
+    Ok(())
+}
+
+/// A GIF that loops forever, encoded a frame at a time as they come.
+pub struct GifWriter {
+    encoder: GifEncoder<BufWriter<File>>,
+    delay: Delay,
+}
+
+impl GifWriter {
+    pub fn create(path: &Path, delay: Duration) -> Result<GifWriter> {
+        let mut encoder = GifEncoder::new_with_speed(BufWriter::new(File::create(path)?), 10);
+        encoder.set_repeat(Repeat::Infinite)?;
+        Ok(GifWriter {
+            encoder,
+            delay: Delay::from_saturating_duration(delay),
+        })
+    }
+
+    /// Encodes `frame`, shown for the delay of the whole GIF.
+    pub fn write(&mut self, frame: RgbImage) -> Result<()> {
+        self.encoder
+            .encode_frame(Frame::from_parts(frame.convert(), 0, 0, self.delay))?;
+        Ok(())
     }
 }
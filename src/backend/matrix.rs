@@ -1,10 +1,8 @@
+use crate::backend::connectivity::Connectivity;
 use crate::cell::cell::{Cell, Position};
+use crate::error::{Error, Result};
 use std::ops::{Index, IndexMut};
 
-type Result<T> = std::result::Result<T, DoubleError>;
-
-pub struct DoubleError;
-
 pub struct MatrixIterator<T: Cell> {
     matrix: Vec<Vec<T>>,
     x_pos: usize,
@@ -27,16 +25,37 @@ impl<T: Cell> Iterator for MatrixIterator<T> {
     fn next(&mut self) -> Option<Self::Item> {
         let curr = self.matrix[self.y_pos][self.x_pos].clone();
         self.x_pos += 1;
-        if self.x_pos as usize == self.matrix[0].len() {
+        if self.x_pos == self.matrix[0].len() {
             self.y_pos += 1;
         }
-        if self.x_pos as usize > self.matrix[0].len() && self.y_pos > self.matrix.len() {
-            return None
+        if self.x_pos > self.matrix[0].len() && self.y_pos > self.matrix.len() {
+            return None;
         }
         Some(curr)
     }
 }
 
+/// An edit to one cell of a loaded maze.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CellUpdate {
+    /// The cell becomes a wall.
+    Wall(Position),
+    /// The cell stops being a wall.
+    Free(Position),
+    /// Entering the cell costs this much from now on; at least 1.
+    Cost(Position, u32),
+}
+
+impl CellUpdate {
+    pub fn position(&self) -> &Position {
+        match self {
+            CellUpdate::Wall(position)
+            | CellUpdate::Free(position)
+            | CellUpdate::Cost(position, _) => position,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Matrix<T: Cell> {
     matrix: Vec<Vec<T>>,
@@ -61,42 +80,121 @@ impl<T: Cell> Matrix<T> {
         self.x_size
     }
 
-    pub fn get_neighbours(&self, position: &T) -> Vec<Result<T>> {
-        let mut arr: Vec<Result<T>> = Vec::new();
+    /// Whether every free cell costs the same to enter.
+    pub fn is_uniform_cost(&self) -> bool {
+        let mut cells = self.matrix.iter().flatten().filter(|cell| !cell.is_wall());
+        match cells.next() {
+            Some(first) => cells.all(|cell| cell.get_cost() == first.get_cost()),
+            None => true,
+        }
+    }
 
-        let test_y = position.y() as i32;
-        let test_x = position.x() as i32;
+    /// Whether the position is inside the matrix and not a wall.
+    pub fn is_walkable(&self, y_pos: i64, x_pos: i64) -> bool {
+        y_pos >= 0
+            && x_pos >= 0
+            && (y_pos as usize) < self.y_size
+            && (x_pos as usize) < self.x_size
+            && !self.matrix[y_pos as usize][x_pos as usize].is_wall()
+    }
 
-        let pos_y = position.y();
-        let pos_x = position.x();
-        if test_x - 1 > 0 && (((test_y + 1) as usize) < self.y_size) {
-            arr.push(Ok(self[(pos_y + 1, pos_x - 1)].clone()));
-        }
-        if ((test_y + 1) as usize) < self.y_size {
-            arr.push(Ok(self[(pos_y + 1, pos_x)].clone()));
-        }
-        if ((test_y + 1) as usize) < self.y_size && ((test_x + 1) as usize) < self.x_size {
-            arr.push(Ok(self[(pos_y + 1, pos_x + 1)].clone()));
-        }
-        if ((test_x - 1) as usize) < self.x_size {
-            arr.push(Ok(self[(pos_y, pos_x - 1)].clone()));
-        }
-        if ((test_x + 1) as usize) < self.x_size {
-            arr.push(Ok(self[(pos_y, pos_x + 1)].clone()));
-        }
-        if test_x - 1 > 0 && test_y - 1 > 0 {
-            arr.push(Ok(self[(pos_y - 1, pos_x - 1)].clone()));
+    /// Whether a straight line between the centres of two cells stays clear
+    /// of walls. Where it crosses a corner exactly, the two cells beside the
+    /// corner are judged as for a diagonal step under `connectivity`.
+    pub fn line_of_sight(
+        &self,
+        from: &Position,
+        to: &Position,
+        connectivity: &Connectivity,
+    ) -> bool {
+        let line = from.line_to(to);
+        for step in line.windows(2) {
+            let (previous, next) = (&step[0], &step[1]);
+            if !self.is_walkable(next.y() as i64, next.x() as i64) {
+                return false;
+            }
+            let (d_y, d_x) = (next.i32y() - previous.i32y(), next.i32x() - previous.i32x());
+            if d_y != 0 && d_x != 0 {
+                let first_wall = self.is_wall_at(previous, d_y, 0);
+                let second_wall = self.is_wall_at(previous, 0, d_x);
+                if (first_wall || second_wall)
+                    && !connectivity.allows_corner(first_wall, second_wall)
+                {
+                    return false;
+                }
+            }
         }
-        if test_y - 1 > 0 {
-            arr.push(Ok(self[(pos_y - 1, pos_x)].clone()));
+        true
+    }
+
+    pub fn get(&self, position: &Position) -> Result<&T> {
+        self.matrix
+            .get(position.y() as usize)
+            .and_then(|row| row.get(position.x() as usize))
+            .ok_or(Error::OutOfBounds(*position))
+    }
+
+    /// Applies an edit to the matrix. Gives whether the cell changed.
+    pub fn apply(&mut self, update: &CellUpdate) -> Result<bool> {
+        let position = *update.position();
+        let cell = self.get(&position)?;
+        let (wall, cost) = match *update {
+            CellUpdate::Wall(_) => (true, cell.get_cost()),
+            CellUpdate::Free(_) => (false, cell.get_cost()),
+            CellUpdate::Cost(_, 0) => {
+                return Err(Error::InvalidOptions(String::from(
+                    "a cell must cost at least 1",
+                )))
+            }
+            CellUpdate::Cost(_, cost) => (cell.is_wall(), cost),
+        };
+        if wall == cell.is_wall() && cost == cell.get_cost() {
+            return Ok(false);
         }
-        if test_y - 1 > 0 && (((test_x + 1) as usize) < self.x_size) {
-            arr.push(Ok(self[(pos_y - 1, pos_x + 1)].clone()));
+        let mut cell = T::new(position.y(), position.x(), wall as u8);
+        cell.set_cost(cost);
+        self[&position] = cell;
+        Ok(true)
+    }
+
+    pub fn get_neighbours(&self, position: &T, connectivity: &Connectivity) -> Vec<T> {
+        let mut arr: Vec<T> = Vec::new();
+
+        for (d_y, d_x) in connectivity.offsets() {
+            if let Some(neighbour) = self.offset(position.get_position(), d_y, d_x) {
+                if d_y != 0 && d_x != 0 {
+                    let first_wall = self.is_wall_at(position.get_position(), d_y, 0);
+                    let second_wall = self.is_wall_at(position.get_position(), 0, d_x);
+                    if !connectivity.allows_corner(first_wall, second_wall) {
+                        continue;
+                    }
+                }
+                arr.push(self[&neighbour].clone());
+            }
         }
 
         arr
     }
 
+    fn offset(&self, position: &Position, d_y: i32, d_x: i32) -> Option<Position> {
+        let y_pos = position.i32y() + d_y;
+        let x_pos = position.i32x() + d_x;
+        if y_pos < 0 || x_pos < 0 || y_pos as usize >= self.y_size || x_pos as usize >= self.x_size
+        {
+            return None;
+        }
+        Some(Position {
+            position: (y_pos as u32, x_pos as u32),
+        })
+    }
+
+    fn is_wall_at(&self, position: &Position, d_y: i32, d_x: i32) -> bool {
+        match self.offset(position, d_y, d_x) {
+            Some(neighbour) => self[&neighbour].is_wall(),
+            None => true,
+        }
+    }
+
     pub fn show(&self) {
         for index_y in 0..self.y_size {
             for index_x in 0..self.x_size {
@@ -122,7 +220,7 @@ impl<T: Cell> Index<(u32, u32)> for Matrix<T> {
     fn index(&self, (y, x): (u32, u32)) -> &Self::Output {
         let y_pos = y as usize;
         let x_pos = x as usize;
-        assert!(y_pos < self.y_size || x_pos < self.x_size );
+        assert!(y_pos < self.y_size && x_pos < self.x_size);
         &self.matrix[y_pos][x_pos]
     }
 }
@@ -131,7 +229,7 @@ impl<T: Cell> Index<(usize, usize)> for Matrix<T> {
     type Output = T;
 
     fn index(&self, (y, x): (usize, usize)) -> &Self::Output {
-        assert!(y < self.y_size || x < self.x_size );
+        assert!(y < self.y_size && x < self.x_size);
         &self.matrix[y][x]
     }
 }
@@ -140,7 +238,7 @@ impl<T: Cell> IndexMut<(u32, u32)> for Matrix<T> {
     fn index_mut(&mut self, (y, x): (u32, u32)) -> &mut Self::Output {
         let y_pos = y as usize;
         let x_pos = x as usize;
-        assert!(y_pos < self.y_size || x_pos < self.x_size );
+        assert!(y_pos < self.y_size && x_pos < self.x_size);
         &mut self.matrix[y_pos][x_pos]
     }
 }
@@ -151,7 +249,7 @@ impl<T: Cell> Index<&Position> for Matrix<T> {
     fn index(&self, pos: &Position) -> &Self::Output {
         let y_pos = pos.y() as usize;
         let x_pos = pos.x() as usize;
-        assert!(y_pos < self.y_size || x_pos < self.x_size );
+        assert!(y_pos < self.y_size && x_pos < self.x_size);
         &self.matrix[y_pos][x_pos]
     }
 }
@@ -160,7 +258,7 @@ impl<T: Cell> IndexMut<&Position> for Matrix<T> {
     fn index_mut(&mut self, pos: &Position) -> &mut Self::Output {
         let y_pos = pos.y() as usize;
         let x_pos = pos.x() as usize;
-        assert!(y_pos < self.y_size || x_pos < self.x_size );
+        assert!(y_pos < self.y_size && x_pos < self.x_size);
         &mut self.matrix[y_pos][x_pos]
     }
 }
@@ -1,72 +1,317 @@
+pub mod connectivity;
+pub mod export;
 pub mod file_handler;
-pub(crate) mod matrix;
+pub mod matrix;
+pub mod svg;
+pub mod terrain;
 
-use image::{GenericImageView, Pixel, Rgb, RgbImage};
-use std::{
-    cmp,
-    io::{self},
+use image::{DynamicImage, GenericImageView, Pixel, Rgb, RgbImage};
+use std::{cmp, collections::HashSet, path::Path};
+
+use self::{
+    file_handler::{read_image_path, FileHandler},
+    terrain::Terrain,
 };
 
-use self::file_handler::FileHandler;
+use crate::{
+    backend::matrix::Matrix,
+    cell::cell::Cell,
+    cell::cell::Position,
+    djikstra::{cbs::Agent, cbs::Plan, Route, SearchResult, Stepper},
+    error::{Error, Result},
+};
 
-use crate::{backend::matrix::Matrix, cell::cell::Cell, cell::cell::Position};
+/// Colours of the routes drawn by `render_routes`, the first for the
+/// shortest.
+pub const ROUTE_COLOURS: [[u8; 3]; 6] = [
+    [255, 0, 0],
+    [0, 0, 255],
+    [255, 140, 0],
+    [160, 0, 200],
+    [0, 170, 170],
+    [200, 0, 120],
+];
 
-pub struct DataHandle<'a, T: Cell> {
+/// A maze loaded from an image, cropped to its outer walls.
+pub struct DataHandle<T: Cell> {
     name: String,
     image: RgbImage,
     diff_x: u32,
     diff_y: u32,
     top_corner: Position,
     bottom_corner: Position,
-    start: Position,
-    end: Position,
     matrix: Matrix<T>,
-    file_handler: &'a FileHandler,
 }
 
-impl<'a, T: Cell> DataHandle<'a, T> {
-    pub fn matrix(&self) -> Matrix<T> {
-        self.matrix.clone()
+impl<T: Cell> DataHandle<T> {
+    pub fn name(&self) -> &str {
+        &self.name
     }
 
-    pub fn write_image(&mut self, path: &Vec<T>, matrix: &Matrix<T>) {
+    pub fn matrix(&self) -> &Matrix<T> {
+        &self.matrix
+    }
+
+    /// Draws the visited cells green and the path, if any, red on a copy of
+    /// the source image. Cells visited by a backward search are drawn cyan
+    /// and the cell where both searches met yellow. Waypoints, the stops of
+    /// a route or the corners of an any-angle path, are drawn magenta.
+    pub fn render(&self, result: &SearchResult) -> RgbImage {
+        let mut image = self.image.clone();
         for y_pos in self.top_corner.y()..(self.bottom_corner.y() - 1) {
             for x_pos in self.top_corner.x()..(self.bottom_corner.x() - 1) {
-                if matrix[(y_pos - (self.diff_y), x_pos - (self.diff_x))].get_visited() {
-                    self.image.put_pixel(x_pos, y_pos, Rgb([0, 255, 0]));
+                let position = Position {
+                    position: (y_pos - self.diff_y, x_pos - self.diff_x),
+                };
+                if result.state.is_visited(&position) {
+                    image.put_pixel(x_pos, y_pos, Rgb([0, 255, 0]));
+                } else if result
+                    .backward
+                    .as_ref()
+                    .is_some_and(|backward| backward.is_visited(&position))
+                {
+                    image.put_pixel(x_pos, y_pos, Rgb([0, 200, 255]));
                 }
             }
         }
-        for each in path {
-            self.image.put_pixel(
+        for each in result.path.iter().flatten() {
+            image.put_pixel(
                 each.x() + (self.diff_x),
                 each.y() + (self.diff_y),
                 Rgb([255, 0, 0]),
             );
         }
-        self.file_handler.write_image(&self.image, "output")
+        if let Some(meeting) = &result.meeting {
+            let (x_pos, y_pos) = self.to_image(meeting);
+            image.put_pixel(x_pos, y_pos, Rgb([255, 255, 0]));
+        }
+        for each in result.waypoints.iter().flatten() {
+            let (x_pos, y_pos) = self.to_image(each);
+            image.put_pixel(x_pos, y_pos, Rgb([255, 0, 255]));
+        }
+        image
+    }
+
+    /// Draws each route in its own colour on a copy of the source image,
+    /// cycling through `ROUTE_COLOURS`. Where routes overlap the first one
+    /// given stays visible.
+    pub fn render_routes(&self, routes: &[Route]) -> RgbImage {
+        let mut image = self.image.clone();
+        for (index, route) in routes.iter().enumerate().rev() {
+            let colour = Rgb(ROUTE_COLOURS[index % ROUTE_COLOURS.len()]);
+            for each in &route.path {
+                let (x_pos, y_pos) = self.to_image(each);
+                image.put_pixel(x_pos, y_pos, colour);
+            }
+        }
+        image
+    }
+
+    /// Runs `stepper` to the end, handing `sink` one frame every `stride`
+    /// expansions and a last one of the finished search. Each frame shows
+    /// the cells reached so far as `render` does, with those still on the
+    /// open list orange and the path to the cell expanded last red.
+    ///
+    /// Frames are drawn one at a time onto a copy of a single canvas that
+    /// each expansion paints its cells onto, so memory does not grow with
+    /// their number.
+    pub fn render_search(
+        &self,
+        mut stepper: Stepper<'_, T>,
+        stride: usize,
+        mut sink: impl FnMut(RgbImage) -> Result<()>,
+    ) -> Result<SearchResult> {
+        let stride = cmp::max(stride, 1);
+        let mut canvas = self.image.clone();
+        let mut expanded = 0;
+        while let Some(step) = stepper.next() {
+            for each in step.pushed.iter().chain(Some(&step.position)) {
+                let (x_pos, y_pos) = self.to_image(each);
+                canvas.put_pixel(x_pos, y_pos, Rgb([0, 255, 0]));
+            }
+            if expanded % stride == 0 {
+                let mut image = canvas.clone();
+                for each in stepper.partial_path().iter().flatten() {
+                    let (x_pos, y_pos) = self.to_image(each);
+                    image.put_pixel(x_pos, y_pos, Rgb([255, 0, 0]));
+                }
+                for each in stepper.state().frontier() {
+                    let (x_pos, y_pos) = self.to_image(&each);
+                    image.put_pixel(x_pos, y_pos, Rgb([255, 165, 0]));
+                }
+                sink(image)?;
+            }
+            expanded += 1;
+        }
+        let result = stepper.into_result();
+        sink(self.render(&result))?;
+        Ok(result)
+    }
+
+    /// One frame for every `stride` time steps of `plan`, and one for the
+    /// end, drawn as they are taken. Each agent is drawn as a square in its colour from
+    /// `ROUTE_COLOURS`, trailing the path it has taken so far.
+    pub fn render_agents<'a>(
+        &'a self,
+        plan: &'a Plan,
+        stride: usize,
+    ) -> impl Iterator<Item = RgbImage> + 'a {
+        let mut times: Vec<usize> = (0..plan.makespan).step_by(cmp::max(stride, 1)).collect();
+        times.push(plan.makespan);
+        times.into_iter().map(move |time| {
+            let mut image = self.image.clone();
+            for (agent, path) in plan.paths.iter().enumerate() {
+                let colour = Rgb(ROUTE_COLOURS[agent % ROUTE_COLOURS.len()]);
+                for each in &path[..cmp::min(time + 1, path.len())] {
+                    let (x_pos, y_pos) = self.to_image(each);
+                    image.put_pixel(x_pos, y_pos, colour);
+                }
+                let (x_pos, y_pos) = self.to_image(&plan.position(agent, time));
+                for y_pos in y_pos.saturating_sub(1)..cmp::min(y_pos + 2, image.height()) {
+                    for x_pos in x_pos.saturating_sub(1)..cmp::min(x_pos + 2, image.width()) {
+                        image.put_pixel(x_pos, y_pos, colour);
+                    }
+                }
+            }
+            image
+        })
+    }
+
+    /// Renders the matrix as text, one row per line: 1 for walls, 0 for
+    /// free cells, a blank for visited cells and 5 for the path.
+    pub fn render_text(&self, result: &SearchResult) -> String {
+        let path: HashSet<&Position> = result.path.iter().flatten().collect();
+        let mut text = String::new();
+        for y_pos in 0..self.matrix.y_size() as u32 {
+            for x_pos in 0..self.matrix.x_size() as u32 {
+                let position = Position::new(y_pos, x_pos);
+                text.push_str(if self.matrix[&position].is_wall() {
+                    " 1 "
+                } else if path.contains(&position) {
+                    " 5 "
+                } else if result.is_visited(&position) {
+                    "   "
+                } else {
+                    " 0 "
+                });
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Converts a pixel of the source image to matrix coordinates.
+    pub fn to_matrix(&self, x_pos: u32, y_pos: u32) -> Result<Position> {
+        let position = Position::new(
+            y_pos.wrapping_sub(self.diff_y),
+            x_pos.wrapping_sub(self.diff_x),
+        );
+        self.matrix.get(&position)?;
+        Ok(position)
     }
 
-    pub fn get_start(&self) -> Position {
+    /// Converts matrix coordinates to `(x, y)` of the source image.
+    pub fn to_image(&self, position: &Position) -> (u32, u32) {
+        (position.x() + self.diff_x, position.y() + self.diff_y)
+    }
+
+    /// Locates the red start marker, in matrix coordinates.
+    pub fn get_start(&self) -> Result<Position> {
         for y_pos in 0..self.image.height() {
             for x_pos in 0..self.image.width() {
                 if test_red(self.image.get_pixel(x_pos, y_pos).channels()) {
-                    return self.create_position(x_pos, y_pos);
+                    return Ok(self.create_position(x_pos, y_pos));
                 }
             }
         }
-        Position { position: (0, 0) }
+        Err(Error::NoStartMarker)
     }
 
-    pub fn get_end(&self) -> Position {
+    /// Locates the blue end marker, in matrix coordinates.
+    pub fn get_end(&self) -> Result<Position> {
         for y_pos in 0..self.image.height() {
             for x_pos in 0..self.image.width() {
                 if test_blue(self.image.get_pixel(x_pos, y_pos).channels()) {
-                    return self.create_position(x_pos, y_pos);
+                    return Ok(self.create_position(x_pos, y_pos));
                 }
             }
         }
-        Position { position: (0, 0) }
+        Err(Error::NoEndMarker)
+    }
+
+    /// Locates every blue marker, for searches with several goals, in the
+    /// order they appear scanning down the image.
+    pub fn get_ends(&self) -> Result<Vec<Position>> {
+        let ends = self.find_markers(test_blue);
+        if ends.is_empty() {
+            return Err(Error::NoEndMarker);
+        }
+        Ok(ends)
+    }
+
+    /// Pairs every red marker with a blue one, both taken in the order they
+    /// appear scanning down the image, as the start and goal of an agent.
+    pub fn get_agents(&self) -> Result<Vec<Agent>> {
+        let starts = self.find_markers(test_red);
+        let goals = self.get_ends()?;
+        if starts.is_empty() {
+            return Err(Error::NoStartMarker);
+        }
+        if starts.len() != goals.len() {
+            return Err(Error::InvalidOptions(format!(
+                "{} start markers but {} end markers",
+                starts.len(),
+                goals.len()
+            )));
+        }
+        Ok(starts
+            .into_iter()
+            .zip(goals)
+            .map(|(start, goal)| Agent { start, goal })
+            .collect())
+    }
+
+    /// Locates the green waypoint markers, in the order they appear
+    /// scanning down the image. Number them that way when the order matters.
+    pub fn get_waypoints(&self) -> Vec<Position> {
+        self.find_markers(test_green)
+    }
+
+    /// One position for each separate patch of pixels passing `test`.
+    /// Patches outside the maze, such as the letters of a label, that lead
+    /// to the same entrance count once.
+    fn find_markers(&self, test: fn(&[u8]) -> bool) -> Vec<Position> {
+        let (width, height) = self.image.dimensions();
+        let mut seen = vec![false; (width * height) as usize];
+        let mut markers = Vec::new();
+        for y_pos in 0..height {
+            for x_pos in 0..width {
+                if seen[(y_pos * width + x_pos) as usize]
+                    || !test(self.image.get_pixel(x_pos, y_pos).channels())
+                {
+                    continue;
+                }
+                let position = self.create_position(x_pos, y_pos);
+                if !markers.contains(&position) {
+                    markers.push(position);
+                }
+                let mut patch = vec![(x_pos, y_pos)];
+                seen[(y_pos * width + x_pos) as usize] = true;
+                while let Some((x_pos, y_pos)) = patch.pop() {
+                    for y_next in y_pos.saturating_sub(1)..cmp::min(y_pos + 2, height) {
+                        for x_next in x_pos.saturating_sub(1)..cmp::min(x_pos + 2, width) {
+                            let index = (y_next * width + x_next) as usize;
+                            if !seen[index] && test(self.image.get_pixel(x_next, y_next).channels())
+                            {
+                                seen[index] = true;
+                                patch.push((x_next, y_next));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        markers
     }
 
     fn create_position(&self, x_pos: u32, y_pos: u32) -> Position {
@@ -170,43 +415,68 @@ impl<'a, T: Cell> DataHandle<'a, T> {
     }
 }
 
-pub(crate) fn get_data<T: Cell>(
+/// Loads `file_name` from the images directory of `file_handler`.
+pub fn get_data<T: Cell>(
     file_handler: &FileHandler,
     file_name: String,
-) -> Result<DataHandle<T>, io::Error> {
-    match file_handler.read_image(&file_name) {
-        Ok(result_image) => {
-            let image = result_image.into_rgb8();
-            let top_corner = get_top_corner(&image);
-            let bottom_corner = get_bottom_corner(&image);
-
-            let diff_x = top_corner.x();
-            let diff_y = top_corner.y();
-
-            let smaller_image = image
-                .view(
-                    top_corner.x(),
-                    top_corner.y(),
-                    bottom_corner.x() - diff_x,
-                    bottom_corner.y() - diff_y,
-                )
-                .to_image();
-
-            return Ok(DataHandle {
-                name: file_name.clone(),
-                image,
-                diff_y,
-                diff_x,
-                top_corner,
-                bottom_corner,
-                start: Position { position: (0, 0) },
-                end: Position { position: (0, 0) },
-                matrix: get_matrix(&smaller_image),
-                file_handler,
-            });
-        }
-        Err(_) => return Err(io::Error::new(io::ErrorKind::InvalidInput, "Wrong input")),
+    terrain: &Terrain,
+) -> Result<DataHandle<T>> {
+    let image = file_handler.read_image(&file_name)?;
+    from_image(file_name, image, terrain)
+}
+
+/// Loads a maze from an image file anywhere on disk.
+pub fn from_path<T: Cell>(path: &Path, terrain: &Terrain) -> Result<DataHandle<T>> {
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    from_image(name, read_image_path(path)?, terrain)
+}
+
+/// Loads a maze from an encoded image held in memory.
+pub fn from_memory<T: Cell>(
+    name: String,
+    buffer: &[u8],
+    terrain: &Terrain,
+) -> Result<DataHandle<T>> {
+    from_image(name, image::load_from_memory(buffer)?, terrain)
+}
+
+/// Builds a maze from an already decoded image.
+pub fn from_image<T: Cell>(
+    name: String,
+    image: DynamicImage,
+    terrain: &Terrain,
+) -> Result<DataHandle<T>> {
+    let image = image.into_rgb8();
+    if !image.pixels().any(|pixel| test_black(pixel.channels())) {
+        return Err(Error::MazeBoundsNotFound);
     }
+    let top_corner = get_top_corner(&image);
+    let bottom_corner = get_bottom_corner(&image);
+
+    let diff_x = top_corner.x();
+    let diff_y = top_corner.y();
+
+    let smaller_image = image
+        .view(
+            top_corner.x(),
+            top_corner.y(),
+            bottom_corner.x() - diff_x,
+            bottom_corner.y() - diff_y,
+        )
+        .to_image();
+
+    Ok(DataHandle {
+        name,
+        image,
+        diff_y,
+        diff_x,
+        top_corner,
+        bottom_corner,
+        matrix: get_matrix(&smaller_image, terrain),
+    })
 }
 
 fn get_top_corner(image: &RgbImage) -> Position {
@@ -263,7 +533,7 @@ fn get_bottom_corner(image: &RgbImage) -> Position {
     }
 }
 
-fn get_matrix<T: Cell>(image: &RgbImage) -> Matrix<T> {
+fn get_matrix<T: Cell>(image: &RgbImage, terrain: &Terrain) -> Matrix<T> {
     let new_size_x = image.width();
     let new_size_y = image.height();
 
@@ -274,27 +544,33 @@ fn get_matrix<T: Cell>(image: &RgbImage) -> Matrix<T> {
             matrix[(y, x)] = T::new(
                 y,
                 x,
-                if test_adjecent(image, x, y) {
+                if !test_marker(pixel) && test_adjecent(image, x, y) {
                     1
                 } else {
                     test_colorus(pixel)
                 },
             );
+            if !matrix[(y, x)].is_wall() {
+                matrix[(y, x)].set_cost(terrain.cost(pixel));
+            }
         }
     }
     matrix
 }
 
 fn test_colorus(pixel: &[u8]) -> u8 {
+    if test_green(pixel) {
+        return 0;
+    }
+    if test_blue(pixel) {
+        return 3;
+    }
     if test_black(pixel) {
         return 1;
     }
     if test_red(pixel) {
         return 2;
     }
-    if test_blue(pixel) {
-        return 3;
-    }
     0
 }
 
@@ -329,8 +605,16 @@ fn test_adjecent(image: &RgbImage, x_pos: u32, y_pos: u32) -> bool {
     false
 }
 
+/// Whether the pixel is a green or blue marker. Pure green and blue have two
+/// equal channels and pass `test_black` too, so the cells they cover are
+/// kept free rather than walled in.
+fn test_marker(pixels: &[u8]) -> bool {
+    test_green(pixels) || test_blue(pixels)
+}
+
 fn test_black(pixels: &[u8]) -> bool {
-    if (pixels[0] == pixels[1] || pixels[0] == pixels[2]) && (pixels[0] <= 160 || pixels[2] <= 160)
+    if (pixels[0] == pixels[1] || pixels[0] == pixels[2])
+        && (pixels[0] <= terrain::WALL_GREY || pixels[2] <= terrain::WALL_GREY)
     {
         return true;
     }
@@ -347,17 +631,13 @@ fn test_black(pixels: &[u8]) -> bool {
 }
 
 fn test_red(pixels: &[u8]) -> bool {
-    if pixels[0] >= 150 && (pixels[1] < 100 || pixels[2] < 100) {
-        true
-    } else {
-        false
-    }
+    pixels[0] >= 150 && (pixels[1] < 100 || pixels[2] < 100)
 }
 
 fn test_blue(pixels: &[u8]) -> bool {
-    if (pixels[0] < 100 || pixels[1] < 100) && pixels[2] >= 150 {
-        true
-    } else {
-        false
-    }
+    (pixels[0] < 100 || pixels[1] < 100) && pixels[2] >= 150
+}
+
+fn test_green(pixels: &[u8]) -> bool {
+    pixels[1] >= 150 && pixels[0] < 100 && pixels[2] < 100
 }
@@ -0,0 +1,173 @@
+use std::fmt::Write;
+
+use image::{codecs::png::PngEncoder, ColorType};
+
+use crate::{
+    backend::DataHandle,
+    cell::cell::{Cell, Position},
+    djikstra::SearchResult,
+    error::Result,
+};
+
+/// What `DataHandle::render_svg` draws besides the path.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SvgOptions {
+    /// Where the SVG finds the maze image. `None` embeds it as a PNG.
+    pub image_href: Option<String>,
+    /// Shade every cell the search reached.
+    pub visited: bool,
+    /// Mark the start and the end, whether or not a path joins them.
+    pub markers: bool,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            image_href: None,
+            visited: false,
+            markers: true,
+        }
+    }
+}
+
+impl<T: Cell> DataHandle<T> {
+    /// Draws `result` as SVG over the source image, in the pixel
+    /// coordinates of the whole uncropped image, so the drawing lines up
+    /// with the image at any zoom. The path is a polyline through the
+    /// centres of its cells, and the markers sit on `start` and `end` even
+    /// when there is no path; each layer is a group with its own id, so it
+    /// can be restyled or hidden.
+    pub fn render_svg(
+        &self,
+        result: &SearchResult,
+        start: &Position,
+        end: &Position,
+        options: &SvgOptions,
+    ) -> Result<String> {
+        let (width, height) = self.image.dimensions();
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink=\"http://www.w3.org/1999/xlink\" \
+             width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">",
+            width, height
+        );
+        let href = match &options.image_href {
+            Some(href) => escape(href),
+            None => {
+                let mut png = Vec::new();
+                PngEncoder::new(&mut png).encode(
+                    self.image.as_raw(),
+                    width,
+                    height,
+                    ColorType::Rgb8,
+                )?;
+                format!("data:image/png;base64,{}", base64(&png))
+            }
+        };
+        let _ = writeln!(
+            svg,
+            "  <image id=\"maze\" x=\"0\" y=\"0\" width=\"{}\" height=\"{}\" xlink:href=\"{}\"/>",
+            width, height, href
+        );
+        if options.visited {
+            self.write_visited(&mut svg, result);
+        }
+        if let Some(path) = &result.path {
+            let points: Vec<String> = path
+                .iter()
+                .map(|position| {
+                    let (x_pos, y_pos) = self.centre(position);
+                    format!("{},{}", x_pos, y_pos)
+                })
+                .collect();
+            let _ = writeln!(
+                svg,
+                "  <polyline id=\"path\" points=\"{}\" fill=\"none\" stroke=\"#ff0000\" \
+                 stroke-width=\"1\" stroke-linejoin=\"round\" stroke-linecap=\"round\"/>",
+                points.join(" ")
+            );
+        }
+        if options.markers {
+            let _ = writeln!(
+                svg,
+                "  <g id=\"markers\" stroke=\"#000000\" stroke-width=\"0.5\">"
+            );
+            for (position, id, colour) in [(start, "start", "#ff0000"), (end, "end", "#0000ff")] {
+                let (x_pos, y_pos) = self.centre(position);
+                let _ = writeln!(
+                    svg,
+                    "    <circle id=\"{}\" cx=\"{}\" cy=\"{}\" r=\"3\" fill=\"{}\"/>",
+                    id, x_pos, y_pos, colour
+                );
+            }
+            let _ = writeln!(svg, "  </g>");
+        }
+        svg.push_str("</svg>\n");
+        Ok(svg)
+    }
+
+    /// One rectangle per horizontal run of visited cells.
+    fn write_visited(&self, svg: &mut String, result: &SearchResult) {
+        let _ = writeln!(
+            svg,
+            "  <g id=\"visited\" fill=\"#00ff00\" fill-opacity=\"0.4\">"
+        );
+        for y_pos in 0..self.matrix.y_size() as u32 {
+            let mut run: Option<u32> = None;
+            for x_pos in 0..=self.matrix.x_size() as u32 {
+                let visited = x_pos < self.matrix.x_size() as u32
+                    && result.is_visited(&Position::new(y_pos, x_pos));
+                match (run, visited) {
+                    (None, true) => run = Some(x_pos),
+                    (Some(first), false) => {
+                        let (left, top) = self.to_image(&Position::new(y_pos, first));
+                        let _ = writeln!(
+                            svg,
+                            "    <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"1\"/>",
+                            left,
+                            top,
+                            x_pos - first
+                        );
+                        run = None;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        let _ = writeln!(svg, "  </g>");
+    }
+
+    /// The centre of a cell in image coordinates.
+    fn centre(&self, position: &Position) -> (f64, f64) {
+        let (x_pos, y_pos) = self.to_image(position);
+        (x_pos as f64 + 0.5, y_pos as f64 + 0.5)
+    }
+}
+
+/// Escapes text for use inside a double-quoted attribute.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Standard base64 with padding.
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (index, byte)| {
+            group | (*byte as u32) << (16 - 8 * index)
+        });
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(ALPHABET[(group >> (18 - 6 * index) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
@@ -0,0 +1,92 @@
+use image::Rgb;
+use std::{cmp, str::FromStr};
+
+use crate::error::Error;
+
+/// Cost of the darkest non-wall pixel when costs come from the grey level.
+pub const MAX_GREY_COST: u32 = 10;
+
+/// Greys at or below this level are walls, so only the lighter ones are
+/// ever charged.
+pub const WALL_GREY: u8 = 160;
+
+/// How the traversal cost of a free cell is derived from its pixel colour.
+///
+/// Every cost is at least 1, so the heuristics stay admissible.
+#[derive(Clone, Debug, Default)]
+pub enum Terrain {
+    /// Every free cell costs 1.
+    #[default]
+    Uniform,
+    /// White costs 1, darker greys cost more, up to `MAX_GREY_COST` for
+    /// the darkest grey lighter than `WALL_GREY`.
+    Grey,
+    /// The cost of the closest colour in the palette. Written
+    /// `palette:RRGGBB=cost,...` on the command line.
+    Palette(Vec<(Rgb<u8>, u32)>),
+}
+
+impl Terrain {
+    pub fn cost(&self, pixel: &[u8]) -> u32 {
+        match self {
+            Terrain::Uniform => 1,
+            Terrain::Grey => {
+                let luma =
+                    (pixel[0] as u32 * 299 + pixel[1] as u32 * 587 + pixel[2] as u32 * 114) / 1000;
+                let lightest = WALL_GREY as u32 + 1;
+                let luma = cmp::max(luma, lightest);
+                1 + (255 - luma) * (MAX_GREY_COST - 1) / (255 - lightest)
+            }
+            Terrain::Palette(palette) => palette
+                .iter()
+                .min_by_key(|(colour, _)| colour_distance(colour, pixel))
+                .map(|(_, cost)| cmp::max(*cost, 1))
+                .unwrap_or(1),
+        }
+    }
+}
+
+impl FromStr for Terrain {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "uniform" => Ok(Terrain::Uniform),
+            "grey" | "gray" => Ok(Terrain::Grey),
+            _ => match name.strip_prefix("palette:") {
+                Some(entries) => entries
+                    .split(',')
+                    .map(palette_entry)
+                    .collect::<Result<_, _>>()
+                    .map(Terrain::Palette),
+                None => Err(Error::InvalidOptions(format!("unknown terrain '{}'", name))),
+            },
+        }
+    }
+}
+
+/// Parses one `RRGGBB=cost` entry of a palette.
+fn palette_entry(entry: &str) -> Result<(Rgb<u8>, u32), Error> {
+    let invalid = || Error::InvalidOptions(format!("invalid palette entry '{}'", entry));
+    let (colour, cost) = entry.split_once('=').ok_or_else(invalid)?;
+    let colour = colour.trim_start_matches('#');
+    if colour.len() != 6 || !colour.is_ascii() {
+        return Err(invalid());
+    }
+    let mut channels = [0; 3];
+    for (index, channel) in channels.iter_mut().enumerate() {
+        *channel =
+            u8::from_str_radix(&colour[2 * index..2 * index + 2], 16).map_err(|_| invalid())?;
+    }
+    let cost = cost.parse().map_err(|_| invalid())?;
+    Ok((Rgb(channels), cost))
+}
+
+fn colour_distance(colour: &Rgb<u8>, pixel: &[u8]) -> u32 {
+    colour
+        .0
+        .iter()
+        .zip(pixel)
+        .map(|(a, b)| (*a as i32 - *b as i32).pow(2) as u32)
+        .sum()
+}
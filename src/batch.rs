@@ -0,0 +1,170 @@
+use std::cmp;
+use std::fmt::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+use crate::backend::{self, file_handler::FileHandler, terrain::Terrain};
+use crate::cell::cell::Cell;
+use crate::djikstra::{find_shortest, SearchOptions, SearchStats};
+use crate::error::{Error, Result};
+
+/// What happened to one maze of a batch.
+pub struct BatchEntry {
+    pub name: String,
+    pub result: Result<BatchSolution>,
+}
+
+pub struct BatchSolution {
+    pub stats: SearchStats,
+}
+
+/// Solves every image in the images directory of `file_handler` and writes
+/// each result to the results directory under the name of its source.
+///
+/// The mazes are shared out over `workers` threads; the entries come back
+/// sorted by name whatever order they finish in.
+pub fn solve_directory<T: Cell>(
+    file_handler: &FileHandler,
+    terrain: &Terrain,
+    options: &SearchOptions,
+    workers: usize,
+) -> Result<Vec<BatchEntry>> {
+    let mut paths = Vec::new();
+    for value in file_handler.read_directory()? {
+        let path = value?.path();
+        if path.is_file() {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..cmp::max(workers, 1) {
+            let sender = sender.clone();
+            let (next, paths) = (&next, &paths);
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let path = match paths.get(index) {
+                    Some(path) => path,
+                    None => break,
+                };
+                let name = path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                let result = solve_one::<T>(file_handler, path, &name, terrain, options);
+                if sender.send((index, BatchEntry { name, result })).is_err() {
+                    break;
+                }
+            });
+        }
+    });
+    drop(sender);
+
+    let mut entries: Vec<(usize, BatchEntry)> = receiver.into_iter().collect();
+    entries.sort_by_key(|(index, _)| *index);
+    Ok(entries.into_iter().map(|(_, entry)| entry).collect())
+}
+
+/// One worker per available core, or a single one if that is unknown.
+pub fn default_workers() -> usize {
+    thread::available_parallelism().map_or(1, |workers| workers.get())
+}
+
+fn solve_one<T: Cell>(
+    file_handler: &FileHandler,
+    path: &Path,
+    name: &str,
+    terrain: &Terrain,
+    options: &SearchOptions,
+) -> Result<BatchSolution> {
+    let index = backend::from_path::<T>(path, terrain)?;
+    let start = index.get_start()?;
+    let end = index.get_end()?;
+    let result = find_shortest(index.matrix(), &start, &end, options)?;
+    file_handler.write_image(&index.render(&result), name)?;
+    match result.path {
+        Some(_) => Ok(BatchSolution {
+            stats: result.stats,
+        }),
+        None => Err(Error::NoPath),
+    }
+}
+
+/// Lays the entries out as an aligned plain-text table.
+pub fn report_table(entries: &[BatchEntry]) -> String {
+    let width = entries
+        .iter()
+        .map(|entry| entry.name.len())
+        .chain(Some(4))
+        .max()
+        .unwrap_or_default();
+    let mut table = String::new();
+    let _ = writeln!(
+        table,
+        "{:<width$}  {:>8}  {:>8}  {:>10}  {:>10}  {:>12}",
+        "maze",
+        "length",
+        "cost",
+        "expanded",
+        "generated",
+        "runtime (ms)",
+        width = width
+    );
+    for entry in entries {
+        let _ = match &entry.result {
+            Ok(solution) => writeln!(
+                table,
+                "{:<width$}  {:>8}  {:>8}  {:>10}  {:>10}  {:>12.3}",
+                entry.name,
+                solution.stats.path_length,
+                solution.stats.path_cost,
+                solution.stats.nodes_expanded,
+                solution.stats.nodes_generated,
+                solution.stats.elapsed.as_secs_f64() * 1000.0,
+                width = width
+            ),
+            Err(error) => writeln!(table, "{:<width$}  {}", entry.name, error, width = width),
+        };
+    }
+    table
+}
+
+/// Lays the entries out as CSV, with the error message in the last column.
+/// `reopenings` is left empty for searches that never reopen nodes.
+pub fn report_csv(entries: &[BatchEntry]) -> String {
+    let mut csv = String::from(
+        "maze,path_length,path_cost,nodes_expanded,nodes_generated,heap_peak,reopenings,runtime_ms,error\n",
+    );
+    for entry in entries {
+        let _ = match &entry.result {
+            Ok(solution) => writeln!(
+                csv,
+                "{},{},{},{},{},{},{},{:.3},",
+                entry.name,
+                solution.stats.path_length,
+                solution.stats.path_cost,
+                solution.stats.nodes_expanded,
+                solution.stats.nodes_generated,
+                solution.stats.heap_peak,
+                solution
+                    .stats
+                    .reopenings
+                    .map(|count| count.to_string())
+                    .unwrap_or_default(),
+                solution.stats.elapsed.as_secs_f64() * 1000.0
+            ),
+            Err(error) => writeln!(
+                csv,
+                "{},,,,,,,,\"{}\"",
+                entry.name,
+                error.to_string().replace('"', "\"\"")
+            ),
+        };
+    }
+    csv
+}
@@ -1,12 +1,16 @@
 use std::cmp;
 use std::fmt::{self, Display, Debug, Formatter};
 
-#[derive(Clone)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Position {
     pub(crate) position: (u32, u32)
 }
 
 impl Position {
+    pub fn new(y: u32, x: u32) -> Position {
+        Position { position: (y, x) }
+    }
+
     pub fn x(&self) -> u32 {
         self.position.1
     }
@@ -26,11 +30,37 @@ impl Position {
     pub fn distance(&self, other: &Self) -> u32 {
         cmp::max(i32::abs(self.i32x() - other.i32x()), i32::abs(self.i32y() - other.i32y())) as u32
     }
-}
 
-impl PartialEq<Self> for Position {
-    fn eq(&self, other: &Self) -> bool {
-        (self.x() == other.x()) && (self.y() == other.y())
+    /// Straight-line distance between the centres of the two cells.
+    pub fn euclidean(&self, other: &Self) -> f64 {
+        let d_x = (self.i32x() - other.i32x()) as f64;
+        let d_y = (self.i32y() - other.i32y()) as f64;
+        f64::sqrt(d_x * d_x + d_y * d_y)
+    }
+
+    /// Every cell a straight line between the centres of the two cells
+    /// passes through, from `self` to `other`. Where the line crosses a
+    /// corner exactly it steps diagonally, skipping the two cells beside it.
+    pub fn line_to(&self, other: &Self) -> Vec<Position> {
+        let (d_x, d_y) = (other.i32x() - self.i32x(), other.i32y() - self.i32y());
+        let (s_x, s_y) = (d_x.signum(), d_y.signum());
+        let (d_x, d_y) = (d_x.abs() as i64, d_y.abs() as i64);
+        let (mut x_pos, mut y_pos) = (self.i32x(), self.i32y());
+        let (mut i_x, mut i_y) = (0, 0);
+        let mut line = vec![*self];
+        while i_x < d_x || i_y < d_y {
+            let decision = (1 + 2 * i_x) * d_y - (1 + 2 * i_y) * d_x;
+            if decision <= 0 {
+                x_pos += s_x;
+                i_x += 1;
+            }
+            if decision >= 0 {
+                y_pos += s_y;
+                i_y += 1;
+            }
+            line.push(Position::new(y_pos as u32, x_pos as u32));
+        }
+        line
     }
 }
 
@@ -40,7 +70,9 @@ impl Display for Position {
     }
 }
 
-pub trait Cell: Display + Debug + Eq + PartialEq<Self> + PartialOrd<Self> + Ord + Clone {
+/// Static map data of one grid cell. Search state lives in
+/// `djikstra::state::SearchState` so a loaded map is never modified by a search.
+pub trait Cell: Display + Debug + Eq + PartialEq<Self> + Clone {
     fn new(x: u32, y: u32, t: u8) -> Self;
 
     fn x(&self) -> u32;
@@ -49,27 +81,11 @@ pub trait Cell: Display + Debug + Eq + PartialEq<Self> + PartialOrd<Self> + Ord
 
     fn get_position(&self) -> &Position;
 
-    fn set_walk(&mut self, walk: bool);
-
-    fn set_visited(&mut self, visited: bool);
-
-    fn get_visited(&self) -> bool;
-
-    fn set_parent(&mut self, parent: Self);
-
-    fn get_parent(&self) -> &Option<(u32, u32)>;
-
-    fn set_distance(&mut self, distance: u32);
-
-    fn get_distance(&self) -> u32;
-
-    fn set_heuristics(&mut self, heuristics: u32);
-
-    fn get_heuristics(&self) -> u32;
-
-    fn get_combined(&self) -> u32;
-
     fn get_type(&self) -> u8;
 
     fn is_wall(&self) -> bool;
+
+    fn set_cost(&mut self, cost: u32);
+
+    fn get_cost(&self) -> u32;
 }
\ No newline at end of file
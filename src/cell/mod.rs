@@ -1,6 +1,6 @@
-pub(crate) mod cell;
+#[allow(clippy::module_inception)]
+pub mod cell;
 
-use std::cmp::{ Ordering };
 use std::fmt::{self, Debug, Display, Formatter};
 use cell::Cell;
 use crate::cell::cell::Position;
@@ -9,10 +9,7 @@ use crate::cell::cell::Position;
 pub struct MyCell {
     position: Position,
     t: u8,
-    distance: u32,
-    heuristics: u32,
-    parent: Option<(u32, u32)>,
-    walk: bool,
+    cost: u32,
 }
 
 impl Cell for MyCell {
@@ -20,10 +17,7 @@ impl Cell for MyCell {
         MyCell {
             position: Position { position: (y, x) },
             t,
-            distance: 0,
-            heuristics: 0,
-            parent: None,
-            walk: false,
+            cost: 1,
         }
     }
 
@@ -39,46 +33,6 @@ impl Cell for MyCell {
         &self.position
     }
 
-    fn set_walk(&mut self, walk: bool) {
-        self.walk = walk;
-    }
-
-    fn set_visited(&mut self, visited: bool) {
-        self.t = if visited { 4 } else { 0 };
-    }
-
-    fn get_visited(&self) -> bool {
-        self.t == 4
-    }
-
-    fn set_parent(&mut self, parent: MyCell) {
-        self.parent = Some((parent.y(), parent.x()));
-    }
-
-    fn get_parent(&self) -> &Option<(u32, u32)> {
-        &self.parent
-    }
-
-    fn set_distance(&mut self, distance: u32) {
-        self.distance = distance
-    }
-
-    fn get_distance(&self) -> u32 {
-        self.distance
-    }
-
-    fn set_heuristics(&mut self, heuristics: u32) {
-        self.heuristics = heuristics
-    }
-
-    fn get_heuristics(&self) -> u32 {
-        self.heuristics
-    }
-
-    fn get_combined(&self) -> u32 {
-        self.heuristics + self.distance
-    }
-
     fn get_type(&self) -> u8 {
         self.t
     }
@@ -86,6 +40,14 @@ impl Cell for MyCell {
     fn is_wall(&self) -> bool {
         self.t == 1
     }
+
+    fn set_cost(&mut self, cost: u32) {
+        self.cost = cost
+    }
+
+    fn get_cost(&self) -> u32 {
+        self.cost
+    }
 }
 
 impl Debug for MyCell {
@@ -96,32 +58,7 @@ impl Debug for MyCell {
 
 impl Display for MyCell {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, " {} ", if self.get_visited() {
-            if self.walk {
-                "5"
-            } else {
-                " "
-            }
-        } else {
-            if self.is_wall() {
-                "1"
-            } else {
-                "0"
-            }
-        })
-    }
-}
-
-impl PartialOrd for MyCell {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-impl Ord for MyCell {
-    fn cmp(&self, other: &Self) -> Ordering {
-        other.get_combined().cmp(&self.get_combined())
-            .then_with(|| self.distance.cmp(&other.distance))
+        write!(f, " {} ", if self.is_wall() { "1" } else { "0" })
     }
 }
 
@@ -0,0 +1,471 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::backend::{connectivity::Connectivity, svg::SvgOptions, terrain::Terrain};
+use crate::batch;
+use crate::djikstra::waypoints::Order;
+use crate::djikstra::{cbs, heuristic, Budget, SearchOptions};
+use crate::error::{Error, Result};
+
+pub const RESULTS: &str = "./resources/results/";
+pub const IMAGES: &str = "./resources/images/";
+
+pub const USAGE: &str = "\
+Usage:
+    djikstra                          choose mazes interactively
+    djikstra solve <input> [options]  solve a single maze image
+    djikstra batch [dir] [options]    solve every image in dir [default: ./resources/images/]
+    djikstra agents <input> [options] move one agent from each red marker to the blue marker
+                                      in the same place in scanning order, without collisions
+    djikstra help                     show this message
+
+Options for solve:
+    -o, --output <file>       where to write the result [default: <input>-solved.<format>]
+    --format <png|txt|gif|svg>
+                              output format; gif animates the search, for astar, dijkstra
+                              and weighted only; svg draws over the source image in its
+                              own coordinates [default: png]
+    --algorithm <name>        astar, dijkstra, jps, bidirectional, weighted, ara, theta
+                              or lazy-theta [default: astar]
+    --heuristic <name>        manhattan, euclidean, octile, chebyshev or zero; a diagonal
+                              costs 1, so only chebyshev and zero suit diagonal moves
+                              [default: chebyshev]
+    --weight <w>              heuristic factor for weighted and ara, at least 1 [default: 1]
+    --time-budget <ms>        stop ara after this many milliseconds; ara prints every path
+                              it improves on, with its bound on the cost
+    --expansion-budget <n>    stop ara after expanding this many nodes
+    --connectivity <name>     4, 8, 8-no-corner-cut or 8-strict [default: 8]
+    --terrain <name>          uniform, grey, or palette:RRGGBB=cost,... charging each cell
+                              the cost of the closest colour [default: uniform]
+    --start <x,y>             start pixel instead of the red marker
+    --end <x,y>               end pixel instead of the blue marker
+    --stops <name>            direct, nearest (the closest of all blue markers), in-order or
+                              optimal (via every green marker) [default: direct]
+    --waypoints <x,y:x,y...>  pixels to pass through instead of the green markers, visited
+                              in this order by --stops in-order
+    --k-shortest <k>          draw the k shortest routes, each in its own colour
+    --diverse <k>             draw k routes that share as few cells as possible
+    --overlap-penalty <p>     extra cost per earlier route through a cell, for
+                              --diverse [default: 1]
+    --frame-step <n>          expansions per frame of a gif [default: 1000]
+    --delay <ms>              how long each frame of a gif shows [default: 50]
+    --image-href <url>        link an svg to this image instead of embedding the maze
+    --layers <list>           comma-separated extra svg layers: visited, markers, or
+                              none [default: markers]
+    --json <file>             also write the path, ends, cost and statistics as JSON, in
+                              matrix and image coordinates
+    --csv <file>              also write the path as CSV, one cell per row
+    --summary-csv <file>      also write the ends, cost and statistics as one CSV row
+
+Options for batch:
+    -o, --output <dir>        where to write the results [default: ./resources/results/]
+    --report <file>           also write the summary table as CSV
+    -j, --jobs <n>            number of worker threads [default: one per core]
+    --algorithm, --heuristic, --weight, --time-budget, --expansion-budget,
+    --connectivity, --terrain as for solve
+
+Options for agents:
+    -o, --output <file>       where to write the animation [default: <input>-agents.gif]
+    --frame-step <n>          time steps per frame [default: 1]
+    --delay <ms>              how long each frame shows [default: 100]
+    --max-nodes <n>           give up after this many constraint tree nodes [default: 10000]
+    --connectivity as for solve";
+
+pub enum Command {
+    Interactive,
+    Solve(Solve),
+    Batch(Batch),
+    Agents(Agents),
+    Help,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Png,
+    Text,
+    Gif,
+    Svg,
+}
+
+impl Format {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Png => "png",
+            Format::Text => "txt",
+            Format::Gif => "gif",
+            Format::Svg => "svg",
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        match name {
+            "png" => Ok(Format::Png),
+            "txt" | "text" => Ok(Format::Text),
+            "gif" => Ok(Format::Gif),
+            "svg" => Ok(Format::Svg),
+            _ => Err(Error::InvalidOptions(format!("unknown format '{}'", name))),
+        }
+    }
+}
+
+pub struct Solve {
+    pub input: PathBuf,
+    pub output: PathBuf,
+    pub format: Format,
+    pub options: SearchOptions,
+    pub terrain: Terrain,
+    pub start: Option<(u32, u32)>,
+    pub end: Option<(u32, u32)>,
+    pub stops: Stops,
+    /// Pixels to visit instead of the waypoint markers.
+    pub waypoints: Option<Vec<(u32, u32)>>,
+    pub routes: Option<Routes>,
+    /// Expansions per frame of an animation.
+    pub frame_step: usize,
+    pub delay: Duration,
+    pub svg: SvgOptions,
+    pub exports: Exports,
+}
+
+/// Machine-readable copies of a solution to write besides the output.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Exports {
+    pub json: Option<PathBuf>,
+    pub csv: Option<PathBuf>,
+    pub summary_csv: Option<PathBuf>,
+}
+
+/// Where the path has to go besides the start.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Stops {
+    /// Straight to the end.
+    #[default]
+    Direct,
+    /// To whichever end marker is cheapest to reach.
+    Nearest,
+    /// Through every waypoint marker, or the given waypoints, on the way to
+    /// the end.
+    Waypoints(Order),
+}
+
+impl FromStr for Stops {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        match name {
+            "direct" => Ok(Stops::Direct),
+            "nearest" => Ok(Stops::Nearest),
+            "in-order" => Ok(Stops::Waypoints(Order::Given)),
+            "optimal" => Ok(Stops::Waypoints(Order::Optimal)),
+            _ => Err(Error::InvalidOptions(format!("unknown stops '{}'", name))),
+        }
+    }
+}
+
+/// Several routes to draw instead of the single shortest path.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Routes {
+    Shortest(usize),
+    Diverse(usize, f64),
+}
+
+pub struct Agents {
+    pub input: PathBuf,
+    pub output: PathBuf,
+    pub connectivity: Connectivity,
+    pub frame_step: usize,
+    pub delay: Duration,
+    pub max_nodes: usize,
+}
+
+pub struct Batch {
+    pub images: String,
+    pub results: String,
+    pub report: Option<PathBuf>,
+    pub workers: usize,
+    pub options: SearchOptions,
+    pub terrain: Terrain,
+}
+
+pub fn parse(args: Vec<String>) -> Result<Command> {
+    let (positional, mut flags) = split(args)?;
+    let command = match positional.first().map(String::as_str) {
+        None => Command::Interactive,
+        Some("help") => Command::Help,
+        Some("solve") => {
+            let input = input(&positional, "solve")?;
+            let format = take(&mut flags, &["--format"])?.unwrap_or(Format::Png);
+            let output = output(
+                &mut flags,
+                &input,
+                &format!("solved.{}", format.extension()),
+            );
+            let solve = Solve {
+                input,
+                output,
+                format,
+                options: search_options(&mut flags)?,
+                terrain: take(&mut flags, &["--terrain"])?.unwrap_or_default(),
+                start: take_point(&mut flags, "--start")?,
+                end: take_point(&mut flags, "--end")?,
+                stops: take(&mut flags, &["--stops"])?.unwrap_or_default(),
+                waypoints: take_points(&mut flags, "--waypoints")?,
+                routes: routes(&mut flags, format)?,
+                frame_step: take_number(&mut flags, "--frame-step")?.unwrap_or(1000),
+                delay: Duration::from_millis(take_number(&mut flags, "--delay")?.unwrap_or(50)),
+                svg: svg_options(&mut flags)?,
+                exports: Exports {
+                    json: remove(&mut flags, &["--json"]).map(PathBuf::from),
+                    csv: remove(&mut flags, &["--csv"]).map(PathBuf::from),
+                    summary_csv: remove(&mut flags, &["--summary-csv"]).map(PathBuf::from),
+                },
+            };
+            if solve.stops != Stops::Direct && solve.routes.is_some() {
+                return Err(Error::InvalidOptions(String::from(
+                    "--stops cannot be combined with --k-shortest or --diverse",
+                )));
+            }
+            if solve.waypoints.is_some() && !matches!(solve.stops, Stops::Waypoints(_)) {
+                return Err(Error::InvalidOptions(String::from(
+                    "--waypoints needs --stops in-order or optimal",
+                )));
+            }
+            if solve.routes.is_some() && solve.exports != Exports::default() {
+                return Err(Error::InvalidOptions(String::from(
+                    "several routes cannot be exported",
+                )));
+            }
+            if solve.stops != Stops::Direct && format == Format::Gif {
+                return Err(Error::InvalidOptions(String::from(
+                    "--stops cannot be animated",
+                )));
+            }
+            Command::Solve(solve)
+        }
+        Some("batch") => Command::Batch(Batch {
+            images: directory(positional.get(1).cloned(), IMAGES),
+            results: directory(remove(&mut flags, &["-o", "--output"]), RESULTS),
+            report: remove(&mut flags, &["--report"]).map(PathBuf::from),
+            workers: match remove(&mut flags, &["-j", "--jobs"]) {
+                Some(jobs) => match jobs.parse() {
+                    Ok(workers) if workers > 0 => workers,
+                    _ => {
+                        return Err(Error::InvalidOptions(format!(
+                            "--jobs expects a positive number but got '{}'",
+                            jobs
+                        )))
+                    }
+                },
+                None => batch::default_workers(),
+            },
+            options: search_options(&mut flags)?,
+            terrain: take(&mut flags, &["--terrain"])?.unwrap_or_default(),
+        }),
+        Some("agents") => {
+            let input = input(&positional, "agents")?;
+            Command::Agents(Agents {
+                output: output(&mut flags, &input, "agents.gif"),
+                input,
+                connectivity: take(&mut flags, &["--connectivity"])?.unwrap_or_default(),
+                frame_step: take_number(&mut flags, "--frame-step")?.unwrap_or(1),
+                delay: Duration::from_millis(take_number(&mut flags, "--delay")?.unwrap_or(100)),
+                max_nodes: take_number(&mut flags, "--max-nodes")?
+                    .unwrap_or(cbs::DEFAULT_MAX_NODES),
+            })
+        }
+        Some(other) => {
+            return Err(Error::InvalidOptions(format!(
+                "unknown command '{}'",
+                other
+            )));
+        }
+    };
+    match flags.keys().next() {
+        Some(flag) => Err(Error::InvalidOptions(format!("unknown option '{}'", flag))),
+        None => Ok(command),
+    }
+}
+
+fn search_options(flags: &mut HashMap<String, String>) -> Result<SearchOptions> {
+    let mut options = SearchOptions::default();
+    if let Some(name) = remove(flags, &["--heuristic"]) {
+        options.heuristic = heuristic::from_name(&name)
+            .ok_or_else(|| Error::InvalidOptions(format!("unknown heuristic '{}'", name)))?;
+    }
+    if let Some(connectivity) = take(flags, &["--connectivity"])? {
+        options.connectivity = connectivity;
+    }
+    if let Some(algorithm) = take(flags, &["--algorithm"])? {
+        options.algorithm = algorithm;
+    }
+    if let Some(weight) = take_number(flags, "--weight")? {
+        options.weight = weight;
+    }
+    let time = take_number(flags, "--time-budget")?;
+    let expansions = take_number(flags, "--expansion-budget")?;
+    options.budget = match (time, expansions) {
+        (Some(_), Some(_)) => {
+            return Err(Error::InvalidOptions(String::from(
+                "--time-budget and --expansion-budget cannot be combined",
+            )))
+        }
+        (Some(millis), None) => Budget::Time(Duration::from_millis(millis)),
+        (None, Some(expansions)) => Budget::Expansions(expansions),
+        (None, None) => Budget::Unlimited,
+    };
+    Ok(options)
+}
+
+fn svg_options(flags: &mut HashMap<String, String>) -> Result<SvgOptions> {
+    let mut options = SvgOptions {
+        image_href: remove(flags, &["--image-href"]),
+        ..SvgOptions::default()
+    };
+    if let Some(layers) = remove(flags, &["--layers"]) {
+        options.markers = false;
+        for layer in layers.split(',').map(str::trim) {
+            match layer {
+                "visited" => options.visited = true,
+                "markers" => options.markers = true,
+                "none" => {}
+                _ => return Err(Error::InvalidOptions(format!("unknown layer '{}'", layer))),
+            }
+        }
+    }
+    Ok(options)
+}
+
+fn routes(flags: &mut HashMap<String, String>, format: Format) -> Result<Option<Routes>> {
+    let shortest = take_number(flags, "--k-shortest")?;
+    let diverse = take_number(flags, "--diverse")?;
+    let penalty = take_number(flags, "--overlap-penalty")?;
+    let routes = match (shortest, diverse) {
+        (Some(_), Some(_)) => {
+            return Err(Error::InvalidOptions(String::from(
+                "--k-shortest and --diverse cannot be combined",
+            )))
+        }
+        (Some(k), None) => Routes::Shortest(k),
+        (None, Some(k)) => Routes::Diverse(k, penalty.unwrap_or(1.0)),
+        (None, None) if penalty.is_some() => {
+            return Err(Error::InvalidOptions(String::from(
+                "--overlap-penalty needs --diverse",
+            )))
+        }
+        (None, None) => return Ok(None),
+    };
+    if format != Format::Png {
+        return Err(Error::InvalidOptions(String::from(
+            "several routes can only be drawn as png",
+        )));
+    }
+    Ok(Some(routes))
+}
+
+fn input(positional: &[String], command: &str) -> Result<PathBuf> {
+    match positional.get(1) {
+        Some(input) => Ok(PathBuf::from(input)),
+        None => Err(Error::InvalidOptions(format!("{} needs an input", command))),
+    }
+}
+
+/// The `-o` flag, or `<input stem>-<suffix>` in the working directory.
+fn output(flags: &mut HashMap<String, String>, input: &Path, suffix: &str) -> PathBuf {
+    if let Some(output) = remove(flags, &["-o", "--output"]) {
+        return PathBuf::from(output);
+    }
+    let stem = input
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    PathBuf::from(format!("{}-{}", stem, suffix))
+}
+
+/// `FileHandler` joins directories and file names without a separator.
+fn directory(path: Option<String>, default: &str) -> String {
+    let mut path = path.unwrap_or_else(|| String::from(default));
+    if !path.ends_with('/') {
+        path.push('/');
+    }
+    path
+}
+
+/// Separates positional arguments from `--flag value` pairs.
+fn split(args: Vec<String>) -> Result<(Vec<String>, HashMap<String, String>)> {
+    let mut positional = Vec::new();
+    let mut flags = HashMap::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "-h" || arg == "--help" {
+            positional.insert(0, String::from("help"));
+        } else if arg.starts_with('-') {
+            match args.next() {
+                Some(value) => {
+                    flags.insert(arg, value);
+                }
+                None => return Err(Error::InvalidOptions(format!("{} needs a value", arg))),
+            }
+        } else {
+            positional.push(arg);
+        }
+    }
+    Ok((positional, flags))
+}
+
+fn remove(flags: &mut HashMap<String, String>, names: &[&str]) -> Option<String> {
+    names.iter().find_map(|name| flags.remove(*name))
+}
+
+fn take<T: FromStr<Err = Error>>(
+    flags: &mut HashMap<String, String>,
+    names: &[&str],
+) -> Result<Option<T>> {
+    remove(flags, names).map(|value| value.parse()).transpose()
+}
+
+fn take_number<T: FromStr>(flags: &mut HashMap<String, String>, name: &str) -> Result<Option<T>> {
+    match flags.remove(name) {
+        Some(value) => match value.parse() {
+            Ok(number) => Ok(Some(number)),
+            Err(_) => Err(Error::InvalidOptions(format!(
+                "{} expects a number but got '{}'",
+                name, value
+            ))),
+        },
+        None => Ok(None),
+    }
+}
+
+fn take_point(flags: &mut HashMap<String, String>, name: &str) -> Result<Option<(u32, u32)>> {
+    match flags.remove(name) {
+        Some(value) => point(name, &value).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// A colon-separated list of x,y points.
+fn take_points(flags: &mut HashMap<String, String>, name: &str) -> Result<Option<Vec<(u32, u32)>>> {
+    match flags.remove(name) {
+        Some(value) => value
+            .split(':')
+            .map(|each| point(name, each))
+            .collect::<Result<_>>()
+            .map(Some),
+        None => Ok(None),
+    }
+}
+
+fn point(name: &str, value: &str) -> Result<(u32, u32)> {
+    let invalid = || Error::InvalidOptions(format!("{} expects x,y but got '{}'", name, value));
+    let (x_pos, y_pos) = value.split_once(',').ok_or_else(invalid)?;
+    match (x_pos.trim().parse(), y_pos.trim().parse()) {
+        (Ok(x_pos), Ok(y_pos)) => Ok((x_pos, y_pos)),
+        _ => Err(invalid()),
+    }
+}
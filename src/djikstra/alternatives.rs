@@ -0,0 +1,242 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::backend::{connectivity::Connectivity, matrix::Matrix};
+use crate::cell::cell::{Cell, Position};
+use crate::error::{Error, Result};
+
+use super::options::SearchOptions;
+use super::state::{Node, SearchState};
+use super::{check_ends, get_parents};
+
+/// One of several routes between the same two cells.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Route {
+    pub path: Vec<Position>,
+    /// What the route really costs, without any overlap penalty.
+    pub cost: u32,
+}
+
+/// Up to `k` loopless routes from `start` to `end` in order of cost, the
+/// first being the shortest, found with Yen's algorithm.
+///
+/// Every further route runs one A* per cell of the route before it, so this
+/// suits small `k` on mazes of moderate size. On open grids the routes tend
+/// to differ by a single cell; see `diverse_routes` for ones that do not.
+/// Only the connectivity of `options` is used: the searches are guided by
+/// exact distances to `end` instead of a heuristic. Gives `Error::NoPath` if
+/// `start` or `end` is a wall.
+pub fn k_shortest<T: Cell>(
+    matrix: &Matrix<T>,
+    start: &Position,
+    end: &Position,
+    options: &SearchOptions,
+    k: usize,
+) -> Result<Vec<Route>> {
+    check_ends(matrix, start, end)?;
+    let to_end = distances_to(matrix, end, &options.connectivity);
+    let search = |from: &Position, rules: &Rules| {
+        constrained_astar(matrix, from, end, &to_end, &options.connectivity, rules)
+    };
+    let mut routes: Vec<Route> = Vec::new();
+    match search(start, &Rules::default()) {
+        Some(path) if k > 0 => routes.push(route(matrix, path)),
+        _ => return Ok(routes),
+    }
+    let mut candidates: Vec<Route> = Vec::new();
+    while routes.len() < k {
+        let previous = routes[routes.len() - 1].path.clone();
+        for spur in 0..previous.len() - 1 {
+            let root = &previous[..=spur];
+            let mut rules = Rules::default();
+            for route in &routes {
+                if route.path.len() > spur + 1 && route.path[..=spur] == *root {
+                    rules
+                        .banned
+                        .insert((route.path[spur], route.path[spur + 1]));
+                }
+            }
+            rules.blocked.extend(root[..spur].iter().copied());
+            if let Some(spur_path) = search(&previous[spur], &rules) {
+                let mut path = root[..spur].to_vec();
+                path.extend(spur_path);
+                let candidate = route(matrix, path);
+                if !routes.contains(&candidate) && !candidates.contains(&candidate) {
+                    candidates.push(candidate);
+                }
+            }
+        }
+        let best = candidates
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, candidate)| (candidate.cost, candidate.path.len()))
+            .map(|(index, _)| index);
+        match best {
+            Some(index) => routes.push(candidates.swap_remove(index)),
+            None => break,
+        }
+    }
+    Ok(routes)
+}
+
+/// Up to `k` routes from `start` to `end` that share as little as possible.
+/// After each route is found, entering any of its cells costs `penalty`
+/// times more for every route already through it, which pushes the next
+/// search elsewhere. Stops early once a search only finds a route it has
+/// already given. Gives `Error::NoPath` if `start` or `end` is a wall.
+pub fn diverse_routes<T: Cell>(
+    matrix: &Matrix<T>,
+    start: &Position,
+    end: &Position,
+    options: &SearchOptions,
+    k: usize,
+    penalty: f64,
+) -> Result<Vec<Route>> {
+    check_ends(matrix, start, end)?;
+    if !penalty.is_finite() || penalty <= 0.0 {
+        return Err(Error::InvalidOptions(format!(
+            "overlap penalty must be above 0, got {}",
+            penalty
+        )));
+    }
+    let mut rules = Rules {
+        penalty,
+        ..Rules::default()
+    };
+    let to_end = distances_to(matrix, end, &options.connectivity);
+    let mut routes: Vec<Route> = Vec::new();
+    while routes.len() < k {
+        let path =
+            match constrained_astar(matrix, start, end, &to_end, &options.connectivity, &rules) {
+                Some(path) => path,
+                None => break,
+            };
+        let route = route(matrix, path);
+        if routes.contains(&route) {
+            break;
+        }
+        for position in &route.path {
+            *rules.uses.entry(*position).or_default() += 1;
+        }
+        routes.push(route);
+    }
+    Ok(routes)
+}
+
+fn route<T: Cell>(matrix: &Matrix<T>, path: Vec<Position>) -> Route {
+    let cost = path
+        .windows(2)
+        .map(|step| step[0].distance(&step[1]) * matrix[&step[1]].get_cost())
+        .sum();
+    Route { path, cost }
+}
+
+/// Restrictions on a single search.
+#[derive(Default)]
+struct Rules {
+    /// Cells the search may not enter.
+    blocked: HashSet<Position>,
+    /// Steps the search may not take.
+    banned: HashSet<(Position, Position)>,
+    /// How many earlier routes run through each cell.
+    uses: HashMap<Position, u32>,
+    penalty: f64,
+}
+
+impl Rules {
+    fn step_cost(&self, cost: u32, to: &Position) -> u32 {
+        match self.uses.get(to) {
+            Some(uses) => cost + (cost as f64 * self.penalty * *uses as f64) as u32,
+            None => cost,
+        }
+    }
+}
+
+/// The cost of the cheapest path from every cell to `end`, found by a
+/// Dijkstra search outwards from it that charges the cell it steps from.
+fn distances_to<T: Cell>(
+    matrix: &Matrix<T>,
+    end: &Position,
+    connectivity: &Connectivity,
+) -> SearchState {
+    let mut state = SearchState::new(matrix);
+    state.set_distance(end, 0);
+    state.open.push(Node {
+        position: *end,
+        distance: 0,
+        combined: 0,
+    });
+    while let Some(current) = state.open.pop() {
+        if current.distance > state.get_distance(&current.position) {
+            continue;
+        }
+        let cell = &matrix[&current.position];
+        for neigbour in matrix.get_neighbours(cell, connectivity) {
+            let position = neigbour.get_position();
+            let cost = current.distance + current.position.distance(position) * cell.get_cost();
+            if !neigbour.is_wall() && cost < state.get_distance(position) {
+                state.set_distance(position, cost);
+                state.open.push(Node {
+                    position: *position,
+                    distance: cost,
+                    combined: cost,
+                });
+            }
+        }
+    }
+    state
+}
+
+/// A* that keeps to `rules`, guided by the exact distances of the
+/// unrestricted grid. Rules only ever remove steps or add cost, so those
+/// distances stay admissible, and they lead each search straight along the
+/// cheapest detour. Cells that cannot reach `end` at all are never entered.
+fn constrained_astar<T: Cell>(
+    matrix: &Matrix<T>,
+    start: &Position,
+    end: &Position,
+    to_end: &SearchState,
+    connectivity: &Connectivity,
+    rules: &Rules,
+) -> Option<Vec<Position>> {
+    let mut state = SearchState::new(matrix);
+    state.set_distance(start, 0);
+    state.open.push(Node {
+        position: *start,
+        distance: 0,
+        combined: to_end.get_distance(start),
+    });
+
+    while let Some(current) = state.open.pop() {
+        if current.distance > state.get_distance(&current.position) {
+            continue;
+        }
+        if current.position == *end {
+            break;
+        }
+        state.close(&current.position);
+        for neigbour in matrix.get_neighbours(&matrix[&current.position], connectivity) {
+            let position = neigbour.get_position();
+            if neigbour.is_wall()
+                || to_end.get_distance(position) == u32::MAX
+                || state.is_closed(position)
+                || rules.blocked.contains(position)
+                || rules.banned.contains(&(current.position, *position))
+            {
+                continue;
+            }
+            let step = current.position.distance(position) * neigbour.get_cost();
+            let cost = current.distance + rules.step_cost(step, position);
+            if cost < state.get_distance(position) {
+                state.set_distance(position, cost);
+                state.set_parent(position, current.position);
+                state.open.push(Node {
+                    position: *position,
+                    distance: cost,
+                    combined: cost + to_end.get_distance(position),
+                });
+            }
+        }
+    }
+
+    get_parents(&state, start, end)
+}
@@ -0,0 +1,225 @@
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use crate::backend::{connectivity::Connectivity, matrix::Matrix};
+use crate::cell::cell::{Cell, Position};
+use crate::error::Result;
+
+use super::heuristic::Heuristic;
+use super::options::SearchOptions;
+use super::state::{Node, SearchState};
+use super::{
+    check_ends, checked_heuristic, finish, get_parents, weighted, ExpansionHook, SearchResult,
+    SearchStats,
+};
+
+/// How much the weight drops between two passes of ARA*.
+pub const WEIGHT_STEP: f64 = 0.5;
+
+/// When an anytime search has to hand back its best path so far.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Budget {
+    /// Keep improving until the path is optimal.
+    #[default]
+    Unlimited,
+    /// Stop once this much time has passed.
+    Time(Duration),
+    /// Stop after expanding this many nodes in total.
+    Expansions(usize),
+}
+
+impl Budget {
+    /// Whether a search started at `timer` that has expanded `expanded`
+    /// nodes must stop.
+    pub fn is_spent(&self, timer: &Instant, expanded: usize) -> bool {
+        match self {
+            Budget::Unlimited => false,
+            Budget::Time(limit) => timer.elapsed() >= *limit,
+            Budget::Expansions(limit) => expanded >= *limit,
+        }
+    }
+}
+
+/// One of the progressively better paths found by ARA*.
+#[derive(Clone, Debug)]
+pub struct AnytimeSolution {
+    pub path: Vec<Position>,
+    pub cost: u32,
+    /// The heuristic weight of the pass that found the path.
+    pub weight: f64,
+    /// The path costs at most this many times the optimal cost.
+    pub bound: f64,
+    /// Nodes expanded over all passes up to this solution.
+    pub nodes_expanded: usize,
+}
+
+/// Anytime Repairing A*: weighted A* run over and over with a shrinking
+/// weight, starting at the weight of `options` and dropping by `WEIGHT_STEP`
+/// down to 1.
+///
+/// Each pass reuses the distances of the previous one and only re-expands
+/// the cells whose distance improved after they were closed. Every pass that
+/// finishes within the budget of `options` adds a solution, so the last one
+/// is the best, and the returned `SearchResult` holds its path. A budget
+/// spent before the first pass finishes leaves no solution and no path.
+pub fn find_ara<T: Cell>(
+    matrix: &Matrix<T>,
+    start: &Position,
+    end: &Position,
+    options: &SearchOptions,
+) -> Result<(Vec<AnytimeSolution>, SearchResult)> {
+    let heuristic = checked_heuristic(options)?;
+    check_ends(matrix, start, end)?;
+    let connectivity = &options.connectivity;
+    let on_expand = options.on_expand.as_ref();
+    let timer = Instant::now();
+    let mut state = SearchState::new(matrix);
+    let mut stats = SearchStats::default();
+    stats.reopened(0);
+    let mut solutions: Vec<AnytimeSolution> = Vec::new();
+    let mut inconsistent: HashSet<Position> = HashSet::new();
+    let mut weight = options.weight;
+
+    state.set_distance(start, 0);
+    state.open.push(Node {
+        position: *start,
+        distance: 0,
+        combined: weighted(heuristic.estimate(start, end), weight),
+    });
+    stats.generated(state.open.len());
+
+    loop {
+        let mut pass = Pass {
+            matrix,
+            state: &mut state,
+            inconsistent: &mut inconsistent,
+            end,
+            heuristic,
+            connectivity,
+            weight,
+            on_expand,
+        };
+        if !pass.improve_path(&mut stats, &options.budget, &timer) {
+            break;
+        }
+        let path = match get_parents(&state, start, end) {
+            Some(path) => path,
+            None => break,
+        };
+        // Parents may have improved since the end was reached, so the path
+        // can be cheaper than the distance recorded for the end.
+        let cost = path
+            .windows(2)
+            .map(|step| step[0].distance(&step[1]) * matrix[&step[1]].get_cost())
+            .sum();
+        let lower_bound = state
+            .frontier()
+            .into_iter()
+            .chain(inconsistent.iter().copied())
+            .map(|position| state.get_distance(&position) + heuristic.estimate(&position, end))
+            .min();
+        let bound = match lower_bound {
+            Some(lower_bound) if lower_bound > 0 => {
+                f64::min(weight, cost as f64 / lower_bound as f64)
+            }
+            _ => 1.0,
+        };
+        solutions.push(AnytimeSolution {
+            path,
+            cost,
+            weight,
+            bound: f64::max(bound, 1.0),
+            nodes_expanded: stats.nodes_expanded,
+        });
+        if bound <= 1.0 || weight <= 1.0 {
+            break;
+        }
+
+        weight = f64::max(weight - WEIGHT_STEP, 1.0);
+        let mut open: HashSet<Position> = state.frontier().into_iter().collect();
+        state.open.clear();
+        stats.reopened(inconsistent.len());
+        open.extend(inconsistent.drain());
+        state.reopen_all();
+        for position in open {
+            let distance = state.get_distance(&position);
+            state.open.push(Node {
+                position,
+                distance,
+                combined: distance + weighted(heuristic.estimate(&position, end), weight),
+            });
+            stats.generated(state.open.len());
+        }
+    }
+
+    let path = solutions.last().map(|solution| solution.path.clone());
+    let mut result = finish(path, stats, state, end);
+    if let Some(solution) = solutions.last() {
+        result.stats.path_cost = solution.cost;
+    }
+    result.stats.elapsed = timer.elapsed();
+    Ok((solutions, result))
+}
+
+/// One weighted A* pass of ARA* over the shared state.
+struct Pass<'a, T: Cell> {
+    matrix: &'a Matrix<T>,
+    state: &'a mut SearchState,
+    /// Cells improved after being closed in this pass, left for the next.
+    inconsistent: &'a mut HashSet<Position>,
+    end: &'a Position,
+    heuristic: &'a dyn Heuristic,
+    connectivity: &'a Connectivity,
+    weight: f64,
+    on_expand: Option<&'a ExpansionHook>,
+}
+
+impl<T: Cell> Pass<'_, T> {
+    /// Expands nodes until no open node can improve on the end. Gives
+    /// `false` if the end was not reached or the budget ran out first.
+    fn improve_path(&mut self, stats: &mut SearchStats, budget: &Budget, timer: &Instant) -> bool {
+        loop {
+            let current = match self.state.open.peek() {
+                Some(current) => *current,
+                None => return self.state.is_visited(self.end),
+            };
+            if current.distance > self.state.get_distance(&current.position)
+                || self.state.is_closed(&current.position)
+            {
+                self.state.open.pop();
+                continue;
+            }
+            if self.state.get_distance(self.end) <= current.combined {
+                return true;
+            }
+            if budget.is_spent(timer, stats.nodes_expanded) {
+                return false;
+            }
+            self.state.open.pop();
+            self.state.close(&current.position);
+            stats.expanded(self.on_expand, &current.position, current.distance);
+            let matrix = self.matrix;
+            for neigbour in matrix.get_neighbours(&matrix[&current.position], self.connectivity) {
+                let position = neigbour.get_position();
+                let cost =
+                    current.distance + current.position.distance(position) * neigbour.get_cost();
+                if neigbour.is_wall() || cost >= self.state.get_distance(position) {
+                    continue;
+                }
+                self.state.set_distance(position, cost);
+                self.state.set_parent(position, current.position);
+                if self.state.is_closed(position) {
+                    self.inconsistent.insert(*position);
+                } else {
+                    self.state.open.push(Node {
+                        position: *position,
+                        distance: cost,
+                        combined: cost
+                            + weighted(self.heuristic.estimate(position, self.end), self.weight),
+                    });
+                    stats.generated(self.state.open.len());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use crate::backend::{connectivity::Connectivity, matrix::Matrix};
+use crate::cell::cell::{Cell, Position};
+
+use super::heuristic::Heuristic;
+use super::state::{Node, SearchState};
+use super::{euclidean_length, get_parents, ExpansionHook, SearchResult, SearchStats};
+
+/// A* run from both ends at once, always expanding the side with the smaller
+/// open list.
+///
+/// The best meeting cell found so far gives an upper bound on the path cost.
+/// The search stops once the lowest key on either open list reaches that
+/// bound: the heuristics are admissible, so no cheaper path can remain.
+///
+/// The result holds the forward search in `state`, the backward one in
+/// `backward`, and the cell where the two halves of the path join in
+/// `meeting`.
+pub fn find_bidirectional<T: Cell>(
+    matrix: &Matrix<T>,
+    start: &Position,
+    end: &Position,
+    heuristic: &dyn Heuristic,
+    connectivity: &Connectivity,
+    on_expand: Option<&ExpansionHook>,
+) -> SearchResult {
+    let mut forward = SearchState::new(matrix);
+    let mut backward = SearchState::new(matrix);
+    let mut stats = SearchStats::default();
+    let mut best: Option<(u32, Position)> = None;
+
+    forward.set_distance(start, 0);
+    forward.open.push(Node {
+        position: *start,
+        distance: 0,
+        combined: heuristic.estimate(start, end),
+    });
+    stats.generated(1);
+    backward.set_distance(end, 0);
+    backward.open.push(Node {
+        position: *end,
+        distance: 0,
+        combined: heuristic.estimate(end, start),
+    });
+    stats.generated(2);
+    if start == end {
+        best = Some((0, *start));
+    }
+
+    loop {
+        let (forward_key, backward_key) = (top_key(&mut forward), top_key(&mut backward));
+        match best {
+            Some((cost, _)) if forward_key.is_none_or(|key| key >= cost) => break,
+            Some((cost, _)) if backward_key.is_none_or(|key| key >= cost) => break,
+            None if forward_key.is_none() || backward_key.is_none() => break,
+            _ => {}
+        }
+        let search = if forward.open.len() <= backward.open.len() {
+            Search {
+                this: &mut forward,
+                other: &backward,
+                target: end,
+                reversed: false,
+            }
+        } else {
+            Search {
+                this: &mut backward,
+                other: &forward,
+                target: start,
+                reversed: true,
+            }
+        };
+        search.expand(
+            matrix,
+            heuristic,
+            connectivity,
+            &mut best,
+            &mut stats,
+            on_expand,
+        );
+    }
+
+    let meeting = best.map(|(_, meeting)| meeting);
+    let path = meeting.and_then(|meeting| {
+        let mut path = get_parents(&forward, start, &meeting)?;
+        let mut to_end = get_parents(&backward, end, &meeting)?;
+        to_end.reverse();
+        path.extend(to_end.into_iter().skip(1));
+        Some(path)
+    });
+    if let (Some(path), Some((cost, _))) = (&path, best) {
+        stats.path_cost = cost;
+        stats.path_length = path.len();
+        stats.euclidean_length = euclidean_length(path);
+    }
+    SearchResult {
+        path,
+        waypoints: None,
+        stats,
+        state: forward,
+        backward: Some(backward),
+        meeting,
+    }
+}
+
+/// The lowest key on the open list, after dropping outdated entries.
+fn top_key(state: &mut SearchState) -> Option<u32> {
+    while let Some(node) = state.open.peek() {
+        if node.distance > state.get_distance(&node.position) {
+            state.open.pop();
+        } else {
+            return Some(node.combined);
+        }
+    }
+    None
+}
+
+/// One direction of the search. Stepping from `a` to `b` costs the cost of
+/// `b` times the step length, so the reversed search charges the cell it
+/// steps from rather than the one it steps to. The heap peak counts both
+/// open lists together.
+struct Search<'a> {
+    this: &'a mut SearchState,
+    other: &'a SearchState,
+    target: &'a Position,
+    reversed: bool,
+}
+
+impl<'a> Search<'a> {
+    fn expand<T: Cell>(
+        self,
+        matrix: &Matrix<T>,
+        heuristic: &dyn Heuristic,
+        connectivity: &Connectivity,
+        best: &mut Option<(u32, Position)>,
+        stats: &mut SearchStats,
+        on_expand: Option<&ExpansionHook>,
+    ) {
+        let current = match self.this.open.pop() {
+            Some(current) => current,
+            None => return,
+        };
+        self.this.close(&current.position);
+        stats.expanded(on_expand, &current.position, current.distance);
+        let current_cell = &matrix[&current.position];
+        for neigbour in matrix.get_neighbours(current_cell, connectivity) {
+            if neigbour.is_wall() {
+                continue;
+            }
+            let position = neigbour.get_position();
+            let entered = if self.reversed {
+                current_cell
+            } else {
+                &neigbour
+            };
+            let cost = current.distance + current.position.distance(position) * entered.get_cost();
+            if cost < self.this.get_distance(position) {
+                self.this.set_distance(position, cost);
+                self.this.set_parent(position, current.position);
+                self.this.open.push(Node {
+                    position: *position,
+                    distance: cost,
+                    combined: cost + heuristic.estimate(position, self.target),
+                });
+                stats.generated(self.this.open.len() + self.other.open.len());
+                if self.other.is_visited(position) {
+                    let total = cost + self.other.get_distance(position);
+                    if best.is_none_or(|(best_cost, _)| total < best_cost) {
+                        *best = Some((total, *position));
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,343 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet, VecDeque};
+
+use crate::backend::{connectivity::Connectivity, matrix::Matrix};
+use crate::cell::cell::{Cell, Position};
+use crate::error::{Error, Result};
+
+use super::check_ends;
+use super::state::SearchState;
+
+/// High-level nodes `find_plan` searches by default before giving up.
+pub const DEFAULT_MAX_NODES: usize = 10_000;
+
+/// One agent to move through the maze.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Agent {
+    pub start: Position,
+    pub goal: Position,
+}
+
+/// Two agents getting in each other's way at `time`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Conflict {
+    /// Both agents are on `position` at once.
+    Vertex {
+        agents: (usize, usize),
+        position: Position,
+        time: usize,
+    },
+    /// The first agent steps from `from` to `to` while the second steps the
+    /// other way.
+    Edge {
+        agents: (usize, usize),
+        from: Position,
+        to: Position,
+        time: usize,
+    },
+}
+
+/// Paths for several agents that never meet. Each path holds where its
+/// agent is at every time step, one step per move or wait, up to its
+/// arrival; the agent then stays at its goal.
+#[derive(Clone, Debug)]
+pub struct Plan {
+    pub paths: Vec<Vec<Position>>,
+    /// Sum of the arrival times of all agents.
+    pub cost: usize,
+    /// Arrival time of the last agent.
+    pub makespan: usize,
+    /// Constraint tree nodes expanded by the high-level search.
+    pub nodes_expanded: usize,
+}
+
+impl Plan {
+    /// Where `agent` is at `time`.
+    pub fn position(&self, agent: usize, time: usize) -> Position {
+        position_at(&self.paths[agent], time)
+    }
+
+    /// The first conflict between any two paths, if any.
+    pub fn first_conflict(&self) -> Option<Conflict> {
+        first_conflict(&self.paths)
+    }
+}
+
+/// Conflict-Based Search: plans every agent on its own with space-time A*,
+/// then, for the first conflict between two paths, tries forbidding it to
+/// either agent and replans that one, cheapest set of paths first. Every
+/// move and every wait takes one time step, whatever the cells cost.
+///
+/// Gives `Error::NoPath` if an agent starts or ends on a wall, cannot reach
+/// its goal at all, or no
+/// conflict-free plan turns up within `max_nodes` high-level nodes.
+pub fn find_plan<T: Cell>(
+    matrix: &Matrix<T>,
+    agents: &[Agent],
+    connectivity: &Connectivity,
+    max_nodes: usize,
+) -> Result<Plan> {
+    for agent in agents {
+        check_ends(matrix, &agent.start, &agent.goal)?;
+    }
+    for (index, agent) in agents.iter().enumerate() {
+        for other in &agents[..index] {
+            if agent.start == other.start || agent.goal == other.goal {
+                return Err(Error::InvalidOptions(String::from(
+                    "agents cannot share a start or a goal",
+                )));
+            }
+        }
+    }
+    let steps: Vec<SearchState> = agents
+        .iter()
+        .map(|agent| steps_to(matrix, &agent.goal, connectivity))
+        .collect();
+    let plan_agent = |agent: usize, constraints: &[Constraint]| {
+        space_time_astar(
+            matrix,
+            connectivity,
+            &agents[agent],
+            &steps[agent],
+            constraints.iter().filter(|each| each.agent == agent),
+        )
+    };
+
+    let mut paths = Vec::new();
+    for agent in 0..agents.len() {
+        paths.push(plan_agent(agent, &[]).ok_or(Error::NoPath)?);
+    }
+    let mut open = BinaryHeap::new();
+    open.push(Reverse((sum_of_costs(&paths), 0)));
+    let mut tree = vec![TreeNode {
+        constraints: Vec::new(),
+        paths,
+    }];
+    let mut nodes_expanded = 0;
+
+    while let Some(Reverse((_, index))) = open.pop() {
+        if nodes_expanded == max_nodes {
+            break;
+        }
+        nodes_expanded += 1;
+        let conflict = match first_conflict(&tree[index].paths) {
+            Some(conflict) => conflict,
+            None => {
+                let paths = std::mem::take(&mut tree[index].paths);
+                return Ok(Plan {
+                    makespan: paths.iter().map(|path| path.len() - 1).max().unwrap_or(0),
+                    cost: sum_of_costs(&paths),
+                    paths,
+                    nodes_expanded,
+                });
+            }
+        };
+        for constraint in Constraint::resolving(&conflict, &tree[index].paths) {
+            let mut constraints = tree[index].constraints.clone();
+            constraints.push(constraint);
+            let path = match plan_agent(constraint.agent, &constraints) {
+                Some(path) => path,
+                None => continue,
+            };
+            let mut paths = tree[index].paths.clone();
+            paths[constraint.agent] = path;
+            open.push(Reverse((sum_of_costs(&paths), tree.len())));
+            tree.push(TreeNode { constraints, paths });
+        }
+    }
+    Err(Error::NoPath)
+}
+
+/// A node of the constraint tree: the constraints added on the way to it
+/// and the cheapest paths that keep to them.
+struct TreeNode {
+    constraints: Vec<Constraint>,
+    paths: Vec<Vec<Position>>,
+}
+
+/// Forbids `agent` to be on a cell, or to take a step, arriving at `time`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Constraint {
+    agent: usize,
+    from: Option<Position>,
+    to: Position,
+    time: usize,
+}
+
+impl Constraint {
+    /// The two constraints that each keep one of the agents out of the
+    /// conflict.
+    fn resolving(conflict: &Conflict, paths: &[Vec<Position>]) -> [Constraint; 2] {
+        match *conflict {
+            Conflict::Vertex {
+                agents: (first, second),
+                position,
+                time,
+            } => [first, second].map(|agent| Constraint {
+                agent,
+                from: None,
+                to: position,
+                time,
+            }),
+            Conflict::Edge {
+                agents: (first, second),
+                time,
+                ..
+            } => [first, second].map(|agent| Constraint {
+                agent,
+                from: Some(position_at(&paths[agent], time - 1)),
+                to: position_at(&paths[agent], time),
+                time,
+            }),
+        }
+    }
+}
+
+fn position_at(path: &[Position], time: usize) -> Position {
+    path[usize::min(time, path.len() - 1)]
+}
+
+fn sum_of_costs(paths: &[Vec<Position>]) -> usize {
+    paths.iter().map(|path| path.len() - 1).sum()
+}
+
+fn first_conflict(paths: &[Vec<Position>]) -> Option<Conflict> {
+    let makespan = paths.iter().map(|path| path.len()).max().unwrap_or(0);
+    for time in 0..makespan {
+        for first in 0..paths.len() {
+            for second in first + 1..paths.len() {
+                let position = position_at(&paths[first], time);
+                if position == position_at(&paths[second], time) {
+                    return Some(Conflict::Vertex {
+                        agents: (first, second),
+                        position,
+                        time,
+                    });
+                }
+                if time > 0
+                    && position == position_at(&paths[second], time - 1)
+                    && position_at(&paths[first], time - 1) == position_at(&paths[second], time)
+                {
+                    return Some(Conflict::Edge {
+                        agents: (first, second),
+                        from: position_at(&paths[first], time - 1),
+                        to: position,
+                        time,
+                    });
+                }
+            }
+        }
+    }
+    None
+}
+
+/// The number of moves from every cell to `goal`, found by a breadth-first
+/// search outwards from it. Cells that cannot reach the goal keep
+/// `u32::MAX`.
+fn steps_to<T: Cell>(
+    matrix: &Matrix<T>,
+    goal: &Position,
+    connectivity: &Connectivity,
+) -> SearchState {
+    let mut state = SearchState::new(matrix);
+    let mut queue = VecDeque::new();
+    if !matrix[goal].is_wall() {
+        state.set_distance(goal, 0);
+        queue.push_back(*goal);
+    }
+    while let Some(current) = queue.pop_front() {
+        let distance = state.get_distance(&current) + 1;
+        for neigbour in matrix.get_neighbours(&matrix[&current], connectivity) {
+            let position = neigbour.get_position();
+            if !neigbour.is_wall() && state.get_distance(position) == u32::MAX {
+                state.set_distance(position, distance);
+                queue.push_back(*position);
+            }
+        }
+    }
+    state
+}
+
+/// An entry of the space-time open list: estimated arrival, then the steps
+/// still to go, so deeper states win ties, then the index of the state in
+/// the arena.
+type Entry = Reverse<(usize, usize, usize)>;
+
+/// A* over `(cell, time)` for one agent, where waiting in place is a move
+/// too. Past the last constraint nothing changes with time, so states later
+/// than that are told apart by cell only, which bounds the search. The agent
+/// only counts as arrived once no constraint can send it off its goal again.
+fn space_time_astar<'a, T: Cell>(
+    matrix: &Matrix<T>,
+    connectivity: &Connectivity,
+    agent: &Agent,
+    steps: &SearchState,
+    constraints: impl Iterator<Item = &'a Constraint>,
+) -> Option<Vec<Position>> {
+    let mut vertex = HashSet::new();
+    let mut edge = HashSet::new();
+    let mut horizon = 0;
+    let mut goal_blocked_until = None;
+    for constraint in constraints {
+        horizon = usize::max(horizon, constraint.time);
+        match constraint.from {
+            Some(from) => {
+                edge.insert((from, constraint.to, constraint.time));
+            }
+            None => {
+                vertex.insert((constraint.to, constraint.time));
+                if constraint.to == agent.goal {
+                    goal_blocked_until = goal_blocked_until.max(Some(constraint.time));
+                }
+            }
+        }
+    }
+
+    let estimate = |position: &Position| match steps.get_distance(position) {
+        u32::MAX => None,
+        steps => Some(steps as usize),
+    };
+    let mut arena: Vec<(Position, usize, Option<usize>)> = vec![(agent.start, 0, None)];
+    let mut open: BinaryHeap<Entry> = BinaryHeap::new();
+    let mut closed = HashSet::new();
+    let remaining = estimate(&agent.start)?;
+    open.push(Reverse((remaining, remaining, 0)));
+
+    while let Some(Reverse((_, _, index))) = open.pop() {
+        let (position, time, _) = arena[index];
+        if !closed.insert((position, usize::min(time, horizon + 1))) {
+            continue;
+        }
+        if position == agent.goal && goal_blocked_until.is_none_or(|until| time > until) {
+            let mut path = Vec::new();
+            let mut current = Some(index);
+            while let Some(index) = current {
+                path.push(arena[index].0);
+                current = arena[index].2;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        let mut moves: Vec<Position> = matrix
+            .get_neighbours(&matrix[&position], connectivity)
+            .iter()
+            .filter(|neighbour| !neighbour.is_wall())
+            .map(|neighbour| *neighbour.get_position())
+            .collect();
+        moves.push(position);
+        for next in moves {
+            let arrival = time + 1;
+            if vertex.contains(&(next, arrival))
+                || edge.contains(&(position, next, arrival))
+                || closed.contains(&(next, usize::min(arrival, horizon + 1)))
+            {
+                continue;
+            }
+            if let Some(remaining) = estimate(&next) {
+                open.push(Reverse((arrival + remaining, remaining, arena.len())));
+                arena.push((next, arrival, Some(index)));
+            }
+        }
+    }
+    None
+}
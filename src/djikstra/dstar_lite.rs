@@ -0,0 +1,56 @@
+use crate::backend::matrix::{CellUpdate, Matrix};
+use crate::cell::cell::{Cell, Position};
+use crate::error::Result;
+
+use super::incremental::{Direction, Planner};
+use super::options::SearchOptions;
+use super::SearchResult;
+
+/// D* Lite: plans backwards from the goal and keeps its distances between
+/// calls, so that after walls appear or disappear only the part of the plan
+/// they affect is searched again.
+///
+/// The planner owns its copy of the matrix. Move the agent with `move_to`,
+/// report edits with `update`, and call `plan` for the repaired path.
+pub struct DStarLite<T: Cell> {
+    planner: Planner<T>,
+}
+
+impl<T: Cell> DStarLite<T> {
+    pub fn new(
+        matrix: Matrix<T>,
+        start: Position,
+        goal: Position,
+        options: SearchOptions,
+    ) -> Result<DStarLite<T>> {
+        Ok(DStarLite {
+            planner: Planner::new(matrix, start, goal, options, Direction::Backward)?,
+        })
+    }
+
+    pub fn matrix(&self) -> &Matrix<T> {
+        self.planner.matrix()
+    }
+
+    pub fn start(&self) -> &Position {
+        self.planner.start()
+    }
+
+    /// Moves the agent, which becomes the start of the next plan.
+    pub fn move_to(&mut self, position: Position) -> Result<()> {
+        self.planner.move_to(position)
+    }
+
+    /// Applies edits to the matrix and marks the cells whose distance to the
+    /// goal they may change. Nothing is searched until the next `plan`.
+    pub fn update(&mut self, updates: &[CellUpdate]) -> Result<()> {
+        self.planner.update(updates)
+    }
+
+    /// Repairs the plan and gives the path from the current start to the
+    /// goal. `stats` counts the expansions of this call only, and `state`
+    /// marks every cell whose distance to the goal is known.
+    pub fn plan(&mut self) -> SearchResult {
+        self.planner.plan()
+    }
+}
@@ -0,0 +1,100 @@
+use std::cmp;
+
+use crate::backend::connectivity::Connectivity;
+use crate::cell::cell::Position;
+
+pub trait Heuristic {
+    /// Estimated cost of moving from `from` to `to`.
+    fn estimate(&self, from: &Position, to: &Position) -> u32;
+
+    /// Whether the estimate never exceeds the real cost of a path through
+    /// the given neighbourhood, where every step, straight or diagonal, costs 1.
+    fn is_admissible(&self, connectivity: &Connectivity) -> bool;
+}
+
+/// Looks up a built-in heuristic by its lower-case name.
+pub fn from_name(name: &str) -> Option<Box<dyn Heuristic + Send + Sync>> {
+    match name {
+        "manhattan" => Some(Box::new(Manhattan)),
+        "euclidean" => Some(Box::new(Euclidean)),
+        "octile" => Some(Box::new(Octile)),
+        "chebyshev" => Some(Box::new(Chebyshev)),
+        "zero" => Some(Box::new(Zero)),
+        _ => None,
+    }
+}
+
+pub struct Manhattan;
+
+pub struct Euclidean;
+
+/// Charges √2 for a diagonal, which the cost model does not: under
+/// 8-connectivity a diagonal step costs 1, so Octile overestimates and is
+/// only admissible under 4-connectivity.
+pub struct Octile;
+
+pub struct Chebyshev;
+
+pub struct Zero;
+
+fn deltas(from: &Position, to: &Position) -> (u32, u32) {
+    (
+        i32::abs(from.i32x() - to.i32x()) as u32,
+        i32::abs(from.i32y() - to.i32y()) as u32,
+    )
+}
+
+impl Heuristic for Manhattan {
+    fn estimate(&self, from: &Position, to: &Position) -> u32 {
+        let (dx, dy) = deltas(from, to);
+        dx + dy
+    }
+
+    fn is_admissible(&self, connectivity: &Connectivity) -> bool {
+        !connectivity.allows_diagonal()
+    }
+}
+
+impl Heuristic for Euclidean {
+    fn estimate(&self, from: &Position, to: &Position) -> u32 {
+        let (dx, dy) = deltas(from, to);
+        f64::sqrt((dx as f64).powi(2) + (dy as f64).powi(2)) as u32
+    }
+
+    fn is_admissible(&self, connectivity: &Connectivity) -> bool {
+        !connectivity.allows_diagonal()
+    }
+}
+
+impl Heuristic for Octile {
+    fn estimate(&self, from: &Position, to: &Position) -> u32 {
+        let (dx, dy) = deltas(from, to);
+        let (min, max) = (cmp::min(dx, dy), cmp::max(dx, dy));
+        (max as f64 + (std::f64::consts::SQRT_2 - 1.0) * min as f64) as u32
+    }
+
+    fn is_admissible(&self, connectivity: &Connectivity) -> bool {
+        !connectivity.allows_diagonal()
+    }
+}
+
+impl Heuristic for Chebyshev {
+    fn estimate(&self, from: &Position, to: &Position) -> u32 {
+        let (dx, dy) = deltas(from, to);
+        cmp::max(dx, dy)
+    }
+
+    fn is_admissible(&self, _connectivity: &Connectivity) -> bool {
+        true
+    }
+}
+
+impl Heuristic for Zero {
+    fn estimate(&self, _from: &Position, _to: &Position) -> u32 {
+        0
+    }
+
+    fn is_admissible(&self, _connectivity: &Connectivity) -> bool {
+        true
+    }
+}
@@ -0,0 +1,558 @@
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use crate::backend::connectivity::Connectivity;
+use crate::backend::matrix::{CellUpdate, Matrix};
+use crate::cell::cell::{Cell, Position};
+use crate::error::{Error, Result};
+
+use super::heuristic::Heuristic;
+use super::incremental::affected_by;
+use super::state::{Node, SearchState};
+use super::{check_admissible, euclidean_length, ExpansionHook, SearchResult, SearchStats};
+
+/// A side for the square clusters that suits most mazes.
+pub const DEFAULT_CLUSTER_SIZE: usize = 32;
+
+/// Runs of border crossings at least this wide get a transition at each end
+/// instead of a single one in the middle.
+const WIDE_ENTRANCE: usize = 6;
+
+/// Directed edges of the abstract graph, from each node.
+type Edges = HashMap<Position, Vec<(Position, u32)>>;
+
+/// Hierarchical path-finding A*: the matrix is cut into square clusters, and
+/// the cells where paths cross from one cluster into the next become the
+/// nodes of a much smaller abstract graph, joined by the cost of crossing
+/// each cluster. Queries search that graph and only then fill in the cells,
+/// one cluster at a time.
+///
+/// Paths are close to, but not always exactly, the shortest. Building the
+/// hierarchy costs more than a single search, so keep it and query it
+/// again. It owns its copy of the matrix; `update` applies edits and
+/// rebuilds only the clusters they touch.
+///
+/// For that reason it is library-only: a single `find_shortest` call, which
+/// is all the command line makes, would pay for the build every time.
+pub struct Hierarchy<T: Cell> {
+    matrix: Matrix<T>,
+    connectivity: Connectivity,
+    cluster_size: usize,
+    clusters_x: usize,
+    clusters_y: usize,
+    /// Crossings picked for each pair of touching clusters, keyed lower
+    /// cluster first, each with its cell in the lower cluster first.
+    transitions: HashMap<(usize, usize), Vec<(Position, Position)>>,
+    /// Costs of crossing each cluster between two of its nodes.
+    intra: Vec<Edges>,
+    /// Steps from one cluster into the next, built from `transitions`.
+    inter: Edges,
+}
+
+impl<T: Cell> Hierarchy<T> {
+    pub fn new(
+        matrix: Matrix<T>,
+        cluster_size: usize,
+        connectivity: Connectivity,
+    ) -> Result<Hierarchy<T>> {
+        if cluster_size < 2 {
+            return Err(Error::InvalidOptions(String::from(
+                "clusters must be at least 2 cells wide",
+            )));
+        }
+        let clusters_x = matrix.x_size().div_ceil(cluster_size);
+        let clusters_y = matrix.y_size().div_ceil(cluster_size);
+        let mut hierarchy = Hierarchy {
+            matrix,
+            connectivity,
+            cluster_size,
+            clusters_x,
+            clusters_y,
+            transitions: HashMap::new(),
+            intra: vec![Edges::new(); clusters_x * clusters_y],
+            inter: Edges::new(),
+        };
+        let all: Vec<usize> = (0..clusters_x * clusters_y).collect();
+        hierarchy.rebuild(&all);
+        Ok(hierarchy)
+    }
+
+    pub fn matrix(&self) -> &Matrix<T> {
+        &self.matrix
+    }
+
+    /// Number of nodes in the abstract graph.
+    pub fn node_count(&self) -> usize {
+        let mut nodes: HashSet<&Position> = self.inter.keys().collect();
+        nodes.extend(self.intra.iter().flat_map(|edges| edges.keys()));
+        nodes.len()
+    }
+
+    /// Applies edits to the matrix and rebuilds the clusters they touch,
+    /// along with any neighbour whose transitions into those changed. Gives
+    /// the clusters rebuilt, numbered row by row from the top left, in
+    /// ascending order.
+    pub fn update(&mut self, updates: &[CellUpdate]) -> Result<Vec<usize>> {
+        let mut touched = HashSet::new();
+        for update in updates {
+            if !self.matrix.apply(update)? {
+                continue;
+            }
+            for position in affected_by(&self.matrix, update.position()) {
+                touched.insert(self.cluster_of(&position));
+            }
+        }
+        let touched: Vec<usize> = touched.into_iter().collect();
+        Ok(self.rebuild(&touched))
+    }
+
+    /// Searches the abstract graph from `start` to `end`, guided by
+    /// `heuristic`, then fills in the cells of every leg.
+    pub fn find_path(
+        &self,
+        start: &Position,
+        end: &Position,
+        heuristic: &dyn Heuristic,
+        on_expand: Option<&ExpansionHook>,
+    ) -> Result<SearchResult> {
+        check_admissible(heuristic, &self.connectivity)?;
+        self.matrix.get(start)?;
+        self.matrix.get(end)?;
+        let mut stats = SearchStats::default();
+        let mut state = SearchState::new(&self.matrix);
+        let blocked = self.matrix[start].is_wall() || self.matrix[end].is_wall();
+        let path = if blocked {
+            None
+        } else {
+            self.abstract_path(start, end, heuristic, &mut stats, on_expand)
+                .and_then(|nodes| self.refine(&nodes, &mut stats, on_expand))
+        };
+        if let Some(path) = &path {
+            let mut distance = 0;
+            state.set_distance(start, 0);
+            for step in path.windows(2) {
+                distance += self.step_cost(&step[0], &step[1]);
+                state.set_distance(&step[1], distance);
+                state.set_parent(&step[1], step[0]);
+            }
+            stats.path_cost = distance;
+            stats.path_length = path.len();
+            stats.euclidean_length = euclidean_length(path);
+        }
+        Ok(SearchResult {
+            path,
+            waypoints: None,
+            stats,
+            state,
+            backward: None,
+            meeting: None,
+        })
+    }
+
+    /// A* over the abstract graph, with `start` and `end` linked into the
+    /// nodes of their clusters for this query only.
+    fn abstract_path(
+        &self,
+        start: &Position,
+        end: &Position,
+        heuristic: &dyn Heuristic,
+        stats: &mut SearchStats,
+        on_expand: Option<&ExpansionHook>,
+    ) -> Option<Vec<Position>> {
+        let start_cluster = self.cluster_of(start);
+        let end_cluster = self.cluster_of(end);
+        let bounds = self.bounds(start_cluster);
+        let from_start = LocalSearch::run(self, bounds, start, None, false, stats, on_expand);
+        let mut leaving: Vec<(Position, u32)> = self
+            .cluster_nodes(start_cluster)
+            .into_iter()
+            .chain(Some(*end).filter(|_| start_cluster == end_cluster))
+            .filter_map(|node| from_start.distance(&node).map(|cost| (node, cost)))
+            .collect();
+        leaving.retain(|(node, _)| node != start);
+        let bounds = self.bounds(end_cluster);
+        let to_end = LocalSearch::run(self, bounds, end, None, true, stats, on_expand);
+        let arriving: HashMap<Position, u32> = self
+            .cluster_nodes(end_cluster)
+            .into_iter()
+            .filter_map(|node| to_end.distance(&node).map(|cost| (node, cost)))
+            .collect();
+
+        let mut distance: HashMap<Position, u32> = HashMap::new();
+        let mut parent: HashMap<Position, Position> = HashMap::new();
+        let mut open = BinaryHeap::new();
+        distance.insert(*start, 0);
+        open.push(Node {
+            position: *start,
+            distance: 0,
+            combined: heuristic.estimate(start, end),
+        });
+        stats.generated(open.len());
+        while let Some(current) = open.pop() {
+            if current.distance > distance[&current.position] {
+                continue;
+            }
+            if current.position == *end {
+                break;
+            }
+            stats.expanded(on_expand, &current.position, current.distance);
+            let mut edges: Vec<(Position, u32)> = Vec::new();
+            if current.position == *start {
+                edges.extend_from_slice(&leaving);
+            } else {
+                let cluster = self.cluster_of(&current.position);
+                edges.extend(
+                    self.intra[cluster]
+                        .get(&current.position)
+                        .into_iter()
+                        .flatten(),
+                );
+            }
+            edges.extend(self.inter.get(&current.position).into_iter().flatten());
+            if let Some(cost) = arriving.get(&current.position) {
+                edges.push((*end, *cost));
+            }
+            for (next, cost) in edges {
+                let cost = current.distance.saturating_add(cost);
+                if cost < *distance.get(&next).unwrap_or(&u32::MAX) {
+                    distance.insert(next, cost);
+                    parent.insert(next, current.position);
+                    open.push(Node {
+                        position: next,
+                        distance: cost,
+                        combined: cost.saturating_add(heuristic.estimate(&next, end)),
+                    });
+                    stats.generated(open.len());
+                }
+            }
+        }
+
+        let mut nodes = vec![*end];
+        let mut last = *end;
+        while last != *start {
+            last = *parent.get(&last)?;
+            nodes.push(last);
+        }
+        nodes.reverse();
+        Some(nodes)
+    }
+
+    /// Fills in the cells between consecutive abstract nodes: a single step
+    /// between clusters, a search confined to the cluster within one.
+    fn refine(
+        &self,
+        nodes: &[Position],
+        stats: &mut SearchStats,
+        on_expand: Option<&ExpansionHook>,
+    ) -> Option<Vec<Position>> {
+        let mut path = vec![nodes[0]];
+        for leg in nodes.windows(2) {
+            let (from, to) = (&leg[0], &leg[1]);
+            let cluster = self.cluster_of(from);
+            if cluster != self.cluster_of(to) {
+                path.push(*to);
+                continue;
+            }
+            let bounds = self.bounds(cluster);
+            let search = LocalSearch::run(self, bounds, from, Some(to), false, stats, on_expand);
+            path.extend(search.path_to(to)?.into_iter().skip(1));
+        }
+        Some(path)
+    }
+
+    /// Recomputes the transitions on every border of the given clusters,
+    /// and the steps across those borders. The crossing costs are redone for
+    /// the given clusters and for the ones across any border whose
+    /// transitions changed.
+    fn rebuild(&mut self, clusters: &[usize]) -> Vec<usize> {
+        let mut borders = HashSet::new();
+        for cluster in clusters {
+            for other in self.touching(*cluster) {
+                borders.insert((usize::min(*cluster, other), usize::max(*cluster, other)));
+            }
+        }
+        let mut rebuilt: HashSet<usize> = clusters.iter().copied().collect();
+        for (lower, upper) in borders {
+            let transitions = entrances(&self.crossings(lower, upper));
+            let old = self.transitions.remove(&(lower, upper)).unwrap_or_default();
+            for (from, to) in &old {
+                self.unlink(from, to);
+                self.unlink(to, from);
+            }
+            for (from, to) in &transitions {
+                self.link(from, to);
+                self.link(to, from);
+            }
+            if transitions != old {
+                rebuilt.insert(lower);
+                rebuilt.insert(upper);
+            }
+            if !transitions.is_empty() {
+                self.transitions.insert((lower, upper), transitions);
+            }
+        }
+
+        for cluster in &rebuilt {
+            let nodes = self.cluster_nodes(*cluster);
+            let mut edges = Edges::new();
+            for node in &nodes {
+                let search = LocalSearch::run(
+                    self,
+                    self.bounds(*cluster),
+                    node,
+                    None,
+                    false,
+                    &mut SearchStats::default(),
+                    None,
+                );
+                let reachable = nodes
+                    .iter()
+                    .filter(|other| *other != node)
+                    .filter_map(|other| search.distance(other).map(|cost| (*other, cost)))
+                    .collect();
+                edges.insert(*node, reachable);
+            }
+            self.intra[*cluster] = edges;
+        }
+        let mut rebuilt: Vec<usize> = rebuilt.into_iter().collect();
+        rebuilt.sort_unstable();
+        rebuilt
+    }
+
+    /// Adds the step from `from` into the next cluster to the abstract graph.
+    fn link(&mut self, from: &Position, to: &Position) {
+        let cost = self.step_cost(from, to);
+        self.inter.entry(*from).or_default().push((*to, cost));
+    }
+
+    fn unlink(&mut self, from: &Position, to: &Position) {
+        if let Some(edges) = self.inter.get_mut(from) {
+            edges.retain(|(next, _)| next != to);
+            if edges.is_empty() {
+                self.inter.remove(from);
+            }
+        }
+    }
+
+    /// Every step from a free cell of `lower` into a free cell of `upper`.
+    fn crossings(&self, lower: usize, upper: usize) -> Vec<(Position, Position)> {
+        let bounds = self.bounds(lower);
+        let mut crossings = Vec::new();
+        for y_pos in bounds.top..bounds.bottom {
+            for x_pos in bounds.left..bounds.right {
+                let on_edge = y_pos == bounds.top
+                    || x_pos == bounds.left
+                    || y_pos + 1 == bounds.bottom
+                    || x_pos + 1 == bounds.right;
+                let cell = &self.matrix[(y_pos, x_pos)];
+                if !on_edge || cell.is_wall() {
+                    continue;
+                }
+                for neighbour in self.matrix.get_neighbours(cell, &self.connectivity) {
+                    let position = neighbour.get_position();
+                    if !neighbour.is_wall() && self.cluster_of(position) == upper {
+                        crossings.push((*cell.get_position(), *position));
+                    }
+                }
+            }
+        }
+        crossings.sort_by_key(|(from, to)| (from.y(), from.x(), to.y(), to.x()));
+        crossings
+    }
+
+    /// The nodes of a cluster: its ends of the transitions on its borders.
+    fn cluster_nodes(&self, cluster: usize) -> Vec<Position> {
+        let mut nodes: Vec<Position> = Vec::new();
+        for other in self.touching(cluster) {
+            let key = (usize::min(cluster, other), usize::max(cluster, other));
+            for (lower, upper) in self.transitions.get(&key).into_iter().flatten() {
+                nodes.push(if cluster == key.0 { *lower } else { *upper });
+            }
+        }
+        nodes.sort_by_key(|node| (node.y(), node.x()));
+        nodes.dedup();
+        nodes
+    }
+
+    /// The up to eight clusters around `cluster`.
+    fn touching(&self, cluster: usize) -> Vec<usize> {
+        let (c_y, c_x) = (cluster / self.clusters_x, cluster % self.clusters_x);
+        let mut touching = Vec::new();
+        for d_y in -1..=1_i64 {
+            for d_x in -1..=1_i64 {
+                let (y_pos, x_pos) = (c_y as i64 + d_y, c_x as i64 + d_x);
+                if (d_y, d_x) != (0, 0)
+                    && y_pos >= 0
+                    && x_pos >= 0
+                    && (y_pos as usize) < self.clusters_y
+                    && (x_pos as usize) < self.clusters_x
+                {
+                    touching.push(y_pos as usize * self.clusters_x + x_pos as usize);
+                }
+            }
+        }
+        touching
+    }
+
+    fn cluster_of(&self, position: &Position) -> usize {
+        (position.y() as usize / self.cluster_size) * self.clusters_x
+            + position.x() as usize / self.cluster_size
+    }
+
+    fn bounds(&self, cluster: usize) -> Bounds {
+        let (c_y, c_x) = (cluster / self.clusters_x, cluster % self.clusters_x);
+        let (top, left) = (c_y * self.cluster_size, c_x * self.cluster_size);
+        Bounds {
+            top: top as u32,
+            left: left as u32,
+            bottom: usize::min(top + self.cluster_size, self.matrix.y_size()) as u32,
+            right: usize::min(left + self.cluster_size, self.matrix.x_size()) as u32,
+        }
+    }
+
+    fn step_cost(&self, from: &Position, to: &Position) -> u32 {
+        from.distance(to) * self.matrix[to].get_cost()
+    }
+}
+
+/// Picks transitions among the crossings between two clusters. Crossings
+/// next to each other on both sides form one entrance, which gets a single
+/// transition in its middle, or one at each end when it is wide.
+fn entrances(crossings: &[(Position, Position)]) -> Vec<(Position, Position)> {
+    let mut runs: Vec<Vec<(Position, Position)>> = Vec::new();
+    for crossing in crossings {
+        let joins = runs.last().and_then(|run| run.last()).is_some_and(|last| {
+            last.0.distance(&crossing.0) <= 1 && last.1.distance(&crossing.1) <= 1
+        });
+        if joins {
+            if let Some(run) = runs.last_mut() {
+                run.push(*crossing);
+            }
+        } else {
+            runs.push(vec![*crossing]);
+        }
+    }
+    let mut transitions = Vec::new();
+    for run in runs {
+        if run.len() >= WIDE_ENTRANCE {
+            transitions.push(run[0]);
+            transitions.push(run[run.len() - 1]);
+        } else {
+            transitions.push(run[run.len() / 2]);
+        }
+    }
+    transitions
+}
+
+/// A rectangle of cells, bottom and right exclusive.
+#[derive(Clone, Copy, Debug)]
+struct Bounds {
+    top: u32,
+    left: u32,
+    bottom: u32,
+    right: u32,
+}
+
+impl Bounds {
+    fn contains(&self, position: &Position) -> bool {
+        position.y() >= self.top
+            && position.y() < self.bottom
+            && position.x() >= self.left
+            && position.x() < self.right
+    }
+
+    fn index(&self, position: &Position) -> usize {
+        ((position.y() - self.top) * (self.right - self.left) + position.x() - self.left) as usize
+    }
+
+    fn size(&self) -> usize {
+        ((self.bottom - self.top) * (self.right - self.left)) as usize
+    }
+}
+
+/// Dijkstra confined to one cluster. A `reversed` search measures the cost
+/// of reaching `from` rather than of leaving it. Its work is counted into
+/// the `stats` it runs with.
+struct LocalSearch {
+    bounds: Bounds,
+    from: Position,
+    distance: Vec<u32>,
+    parent: Vec<Option<Position>>,
+}
+
+impl LocalSearch {
+    fn run<T: Cell>(
+        hierarchy: &Hierarchy<T>,
+        bounds: Bounds,
+        from: &Position,
+        target: Option<&Position>,
+        reversed: bool,
+        stats: &mut SearchStats,
+        on_expand: Option<&ExpansionHook>,
+    ) -> LocalSearch {
+        let matrix = &hierarchy.matrix;
+        let mut search = LocalSearch {
+            bounds,
+            from: *from,
+            distance: vec![u32::MAX; bounds.size()],
+            parent: vec![None; bounds.size()],
+        };
+        let mut open = BinaryHeap::new();
+        search.distance[bounds.index(from)] = 0;
+        open.push(Node {
+            position: *from,
+            distance: 0,
+            combined: 0,
+        });
+        stats.generated(open.len());
+        while let Some(current) = open.pop() {
+            if current.distance > search.distance[bounds.index(&current.position)] {
+                continue;
+            }
+            if Some(&current.position) == target {
+                break;
+            }
+            stats.expanded(on_expand, &current.position, current.distance);
+            let cell = &matrix[&current.position];
+            for neighbour in matrix.get_neighbours(cell, &hierarchy.connectivity) {
+                let position = neighbour.get_position();
+                if neighbour.is_wall() || !bounds.contains(position) {
+                    continue;
+                }
+                let entered = if reversed { cell } else { &neighbour };
+                let cost =
+                    current.distance + current.position.distance(position) * entered.get_cost();
+                let index = bounds.index(position);
+                if cost < search.distance[index] {
+                    search.distance[index] = cost;
+                    search.parent[index] = Some(current.position);
+                    open.push(Node {
+                        position: *position,
+                        distance: cost,
+                        combined: cost,
+                    });
+                    stats.generated(open.len());
+                }
+            }
+        }
+        search
+    }
+
+    fn distance(&self, position: &Position) -> Option<u32> {
+        if !self.bounds.contains(position) {
+            return None;
+        }
+        Some(self.distance[self.bounds.index(position)]).filter(|cost| *cost != u32::MAX)
+    }
+
+    fn path_to(&self, to: &Position) -> Option<Vec<Position>> {
+        self.distance(to)?;
+        let mut path = vec![*to];
+        let mut last = *to;
+        while last != self.from {
+            last = self.parent[self.bounds.index(&last)]?;
+            path.push(last);
+        }
+        path.reverse();
+        Some(path)
+    }
+}
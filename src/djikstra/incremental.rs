@@ -0,0 +1,449 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::time::Instant;
+
+use crate::backend::connectivity::Connectivity;
+use crate::backend::matrix::{CellUpdate, Matrix};
+use crate::cell::cell::{Cell, Position};
+use crate::error::Result;
+
+use super::heuristic::Heuristic;
+use super::options::SearchOptions;
+use super::result::SearchStats;
+use super::state::SearchState;
+use super::{checked_heuristic, euclidean_length, search_heuristic, ExpansionHook, SearchResult};
+
+/// Priority of a cell on the open list of an incremental search: the
+/// estimated total first, the best known distance to break ties.
+pub(crate) type Key = (u32, u32);
+
+/// Which way an incremental search measures its distances.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Direction {
+    /// From the source, as LPA* does from its start.
+    Forward,
+    /// To the source, as D* Lite does to its goal.
+    Backward,
+}
+
+/// An incremental planner between a start and a goal over its own copy of
+/// the matrix, keeping its distances between calls. LPA* runs it forwards
+/// from the start, D* Lite backwards from the goal so that the start can
+/// move.
+pub(crate) struct Planner<T: Cell> {
+    matrix: Matrix<T>,
+    options: SearchOptions,
+    direction: Direction,
+    start: Position,
+    goal: Position,
+    /// Where the start was when the keys on the open list were computed.
+    last: Position,
+    /// How much the heuristic may have shrunk since then, added to new keys.
+    modifier: u32,
+    state: IncrementalState,
+}
+
+impl<T: Cell> Planner<T> {
+    pub fn new(
+        matrix: Matrix<T>,
+        start: Position,
+        goal: Position,
+        options: SearchOptions,
+        direction: Direction,
+    ) -> Result<Planner<T>> {
+        checked_heuristic(&options)?;
+        matrix.get(&start)?;
+        matrix.get(&goal)?;
+        let mut planner = Planner {
+            state: IncrementalState::new(&matrix),
+            matrix,
+            options,
+            direction,
+            start,
+            goal,
+            last: start,
+            modifier: 0,
+        };
+        let (search, state) = planner.parts();
+        state.set_lookahead(&search.source, 0);
+        state.update_cell(&search, &search.source);
+        Ok(planner)
+    }
+
+    pub fn matrix(&self) -> &Matrix<T> {
+        &self.matrix
+    }
+
+    pub fn start(&self) -> &Position {
+        &self.start
+    }
+
+    /// Moves the start. Only a backward planner, whose distances are
+    /// measured to the goal, keeps them valid.
+    pub fn move_to(&mut self, position: Position) -> Result<()> {
+        self.matrix.get(&position)?;
+        self.modifier += search_heuristic(&self.options).estimate(&self.last, &position);
+        self.last = position;
+        self.start = position;
+        Ok(())
+    }
+
+    /// Applies edits to the matrix and marks the cells whose distance they
+    /// may change. Nothing is searched until the next `plan`.
+    pub fn update(&mut self, updates: &[CellUpdate]) -> Result<()> {
+        for update in updates {
+            if !self.matrix.apply(update)? {
+                continue;
+            }
+            let (search, state) = self.parts();
+            for position in affected_by(search.matrix, update.position()) {
+                state.update_cell(&search, &position);
+            }
+        }
+        Ok(())
+    }
+
+    /// Repairs the plan and gives the path from the start to the goal.
+    /// `stats` counts the expansions of this call only, and `state` marks
+    /// every cell whose distance is known.
+    pub fn plan(&mut self) -> SearchResult {
+        let timer = Instant::now();
+        let mut stats = SearchStats::default();
+        stats.reopened(0);
+        let (search, state) = self.parts();
+        state.compute_shortest_path(&search, &mut stats);
+        state.count_into(&mut stats);
+        let blocked =
+            search.matrix[&search.source].is_wall() || search.matrix[&search.target].is_wall();
+        let path = if blocked {
+            None
+        } else {
+            state.extract_path(&search)
+        };
+        if let Some(path) = &path {
+            stats.path_cost = state.get_distance(&search.target);
+            stats.path_length = path.len();
+            stats.euclidean_length = euclidean_length(path);
+        }
+        stats.elapsed = timer.elapsed();
+        SearchResult {
+            path,
+            waypoints: None,
+            stats,
+            state: self.state.to_search_state(&self.matrix),
+            backward: None,
+            meeting: None,
+        }
+    }
+
+    /// The search this planner runs, and the state it keeps for it.
+    fn parts(&mut self) -> (Search<'_, T>, &mut IncrementalState) {
+        let (source, target) = match self.direction {
+            Direction::Forward => (self.start, self.goal),
+            Direction::Backward => (self.goal, self.start),
+        };
+        let search = Search {
+            matrix: &self.matrix,
+            connectivity: &self.options.connectivity,
+            heuristic: search_heuristic(&self.options),
+            on_expand: self.options.on_expand.as_ref(),
+            direction: self.direction,
+            source,
+            target,
+            modifier: self.modifier,
+        };
+        (search, &mut self.state)
+    }
+}
+
+/// What an incremental search needs of the planner running it for one
+/// call: the maze, the rules to search it by and the two ends.
+struct Search<'a, T: Cell> {
+    pub matrix: &'a Matrix<T>,
+    pub connectivity: &'a Connectivity,
+    pub heuristic: &'a dyn Heuristic,
+    pub on_expand: Option<&'a ExpansionHook>,
+    pub direction: Direction,
+    /// The cell every distance is measured from, or to.
+    pub source: Position,
+    /// The cell whose distance the planner wants.
+    pub target: Position,
+    /// Added to every key; D* Lite raises it as the agent moves.
+    pub modifier: u32,
+}
+
+impl<T: Cell> Search<'_, T> {
+    /// The cost of the step between two neighbours, taken from the source
+    /// side to the target side.
+    fn step_cost(&self, near: &Position, far: &Position) -> u32 {
+        let (from, to) = match self.direction {
+            Direction::Forward => (near, far),
+            Direction::Backward => (far, near),
+        };
+        from.distance(to) * self.matrix[to].get_cost()
+    }
+
+    fn estimate(&self, position: &Position) -> u32 {
+        match self.direction {
+            Direction::Forward => self.heuristic.estimate(position, &self.target),
+            Direction::Backward => self.heuristic.estimate(&self.target, position),
+        }
+    }
+
+    fn neighbours(&self, position: &Position) -> Vec<Position> {
+        free_neighbours(self.matrix, position, self.connectivity)
+    }
+}
+
+/// An entry of the open list, ordered by its key, lowest first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Entry {
+    key: Key,
+    position: Position,
+}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Entry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.key.cmp(&self.key)
+    }
+}
+
+/// The bookkeeping D* Lite and LPA* keep between runs: the distance of every
+/// cell and its one-step lookahead, the best distance its neighbours offer.
+/// A cell whose two values disagree was outdated by an edit and waits on the
+/// open list to be expanded again.
+struct IncrementalState {
+    x_size: usize,
+    distance: Vec<u32>,
+    lookahead: Vec<u32>,
+    open: BinaryHeap<Entry>,
+    /// Pushes onto the open list since the last `count_into`.
+    counts: SearchStats,
+}
+
+impl IncrementalState {
+    pub fn new<T: Cell>(matrix: &Matrix<T>) -> IncrementalState {
+        let size = matrix.x_size() * matrix.y_size();
+        IncrementalState {
+            x_size: matrix.x_size(),
+            distance: vec![u32::MAX; size],
+            lookahead: vec![u32::MAX; size],
+            open: BinaryHeap::new(),
+            counts: SearchStats::default(),
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.distance.len()
+    }
+
+    fn index(&self, position: &Position) -> usize {
+        position.y() as usize * self.x_size + position.x() as usize
+    }
+
+    pub fn get_distance(&self, position: &Position) -> u32 {
+        self.distance[self.index(position)]
+    }
+
+    pub fn set_distance(&mut self, position: &Position, distance: u32) {
+        let index = self.index(position);
+        self.distance[index] = distance;
+    }
+
+    pub fn get_lookahead(&self, position: &Position) -> u32 {
+        self.lookahead[self.index(position)]
+    }
+
+    pub fn set_lookahead(&mut self, position: &Position, lookahead: u32) {
+        let index = self.index(position);
+        self.lookahead[index] = lookahead;
+    }
+
+    pub fn is_consistent(&self, position: &Position) -> bool {
+        self.get_distance(position) == self.get_lookahead(position)
+    }
+
+    /// The lower of the distance and the lookahead, on which keys are based.
+    pub fn best(&self, position: &Position) -> u32 {
+        u32::min(self.get_distance(position), self.get_lookahead(position))
+    }
+
+    pub fn push(&mut self, key: Key, position: Position) {
+        self.open.push(Entry { key, position });
+        self.counts.generated(self.open.len());
+    }
+
+    /// Adds the pushes since the last call to `stats`, edits included.
+    pub fn count_into(&mut self, stats: &mut SearchStats) {
+        let counts = std::mem::take(&mut self.counts);
+        stats.nodes_generated += counts.nodes_generated;
+        stats.heap_peak = usize::max(stats.heap_peak, counts.heap_peak);
+    }
+
+    /// The lowest entry on the open list that still needs work, after
+    /// dropping those of consistent cells and those outdated by a later push
+    /// with a lower key. `key` gives the current key of a cell.
+    pub fn top(&mut self, key: impl Fn(&Self, &Position) -> Key) -> Option<(Key, Position)> {
+        while let Some(top) = self.open.peek().copied() {
+            if self.is_consistent(&top.position) || top.key > key(self, &top.position) {
+                self.open.pop();
+            } else {
+                return Some((top.key, top.position));
+            }
+        }
+        None
+    }
+
+    pub fn pop(&mut self) {
+        self.open.pop();
+    }
+
+    /// The key of a cell: its best distance plus the estimate to the target,
+    /// then the best distance alone.
+    pub fn key<T: Cell>(&self, search: &Search<T>, position: &Position) -> Key {
+        let best = self.best(position);
+        (
+            best.saturating_add(search.estimate(position))
+                .saturating_add(search.modifier),
+            best,
+        )
+    }
+
+    /// Expands outdated cells, lowest key first, until the distance of the
+    /// target is settled.
+    pub fn compute_shortest_path<T: Cell>(&mut self, search: &Search<T>, stats: &mut SearchStats) {
+        let target = search.target;
+        while let Some((top, position)) = self.top(|state, position| state.key(search, position)) {
+            if top >= self.key(search, &target) && self.is_consistent(&target) {
+                break;
+            }
+            self.pop();
+            let key = self.key(search, &position);
+            if top < key {
+                self.push(key, position);
+                continue;
+            }
+            stats.expanded(search.on_expand, &position, self.best(&position));
+            let lookahead = self.get_lookahead(&position);
+            if self.get_distance(&position) > lookahead {
+                self.set_distance(&position, lookahead);
+            } else {
+                self.set_distance(&position, u32::MAX);
+                self.update_cell(search, &position);
+                stats.reopened(1);
+            }
+            for neighbour in search.neighbours(&position) {
+                self.update_cell(search, &neighbour);
+            }
+        }
+    }
+
+    /// Recomputes the one-step lookahead of a cell and queues it if it no
+    /// longer agrees with its distance.
+    pub fn update_cell<T: Cell>(&mut self, search: &Search<T>, position: &Position) {
+        if *position != search.source {
+            let lookahead = search
+                .neighbours(position)
+                .iter()
+                .map(|near| {
+                    self.get_distance(near)
+                        .saturating_add(search.step_cost(near, position))
+                })
+                .min()
+                .unwrap_or(u32::MAX);
+            self.set_lookahead(position, lookahead);
+        }
+        if !self.is_consistent(position) {
+            let key = self.key(search, position);
+            self.push(key, *position);
+        }
+    }
+
+    /// Walks from the target to the source, each time onto the neighbour
+    /// closest to the source counting the step onto it, and gives the path
+    /// in the direction it is walked by the planner: from the source for
+    /// LPA*, to the source for D* Lite.
+    pub fn extract_path<T: Cell>(&self, search: &Search<T>) -> Option<Vec<Position>> {
+        if self.get_distance(&search.target) == u32::MAX {
+            return None;
+        }
+        let mut path = vec![search.target];
+        let mut current = search.target;
+        while current != search.source {
+            if path.len() > self.size() {
+                return None;
+            }
+            current = search
+                .neighbours(&current)
+                .into_iter()
+                .filter(|near| self.get_distance(near) != u32::MAX)
+                .min_by_key(|near| {
+                    self.get_distance(near)
+                        .saturating_add(search.step_cost(near, &current))
+                })?;
+            path.push(current);
+        }
+        if search.direction == Direction::Forward {
+            path.reverse();
+        }
+        Some(path)
+    }
+
+    /// A `SearchState` marking every cell whose distance is known, so the
+    /// result can be drawn like that of any other search.
+    pub fn to_search_state<T: Cell>(&self, matrix: &Matrix<T>) -> SearchState {
+        let mut state = SearchState::new(matrix);
+        for y_pos in 0..matrix.y_size() as u32 {
+            for x_pos in 0..matrix.x_size() as u32 {
+                let position = Position::new(y_pos, x_pos);
+                if self.get_distance(&position) != u32::MAX {
+                    state.set_distance(&position, self.get_distance(&position));
+                }
+            }
+        }
+        state
+    }
+}
+
+/// The free cells one step away from a free cell; none for a wall.
+pub(crate) fn free_neighbours<T: Cell>(
+    matrix: &Matrix<T>,
+    position: &Position,
+    connectivity: &Connectivity,
+) -> Vec<Position> {
+    if matrix[position].is_wall() {
+        return Vec::new();
+    }
+    matrix
+        .get_neighbours(&matrix[position], connectivity)
+        .iter()
+        .filter(|neighbour| !neighbour.is_wall())
+        .map(|neighbour| *neighbour.get_position())
+        .collect()
+}
+
+/// The cells an edit of `position` may change the distances of. An edit
+/// alters the steps into the cell, and a wall also the diagonal steps
+/// squeezing past it, all of which start within one cell of it.
+pub(crate) fn affected_by<T: Cell>(matrix: &Matrix<T>, position: &Position) -> Vec<Position> {
+    let mut cells = Vec::new();
+    for d_y in -1..=1 {
+        for d_x in -1..=1 {
+            let (y_pos, x_pos) = (position.i32y() + d_y, position.i32x() + d_x);
+            if y_pos >= 0
+                && x_pos >= 0
+                && (y_pos as usize) < matrix.y_size()
+                && (x_pos as usize) < matrix.x_size()
+            {
+                cells.push(Position::new(y_pos as u32, x_pos as u32));
+            }
+        }
+    }
+    cells
+}
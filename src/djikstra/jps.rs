@@ -0,0 +1,194 @@
+use crate::backend::{connectivity::Connectivity, matrix::Matrix};
+use crate::cell::cell::{Cell, Position};
+use crate::error::{Error, Result};
+
+use super::heuristic::Heuristic;
+use super::state::{Node, SearchState};
+use super::{finish, get_parents, ExpansionHook, SearchResult, SearchStats};
+
+const DIRECTIONS: [(i64, i64); 8] = [
+    (1, 0),
+    (0, -1),
+    (0, 1),
+    (-1, 0),
+    (1, -1),
+    (1, 1),
+    (-1, -1),
+    (-1, 1),
+];
+
+/// Jump Point Search: A* that only puts the cells where an optimal path may
+/// turn on the open list, and jumps straight over everything in between.
+///
+/// Finds paths of the same cost as A* on 8-connected grids where every free
+/// cell costs the same. The returned path still lists every cell.
+pub fn find_jps<T: Cell>(
+    matrix: &Matrix<T>,
+    start: &Position,
+    end: &Position,
+    heuristic: &dyn Heuristic,
+    connectivity: &Connectivity,
+    on_expand: Option<&ExpansionHook>,
+) -> Result<SearchResult> {
+    if *connectivity != Connectivity::Eight {
+        return Err(Error::InvalidOptions(String::from(
+            "jump point search needs 8-connectivity",
+        )));
+    }
+    if !matrix.is_uniform_cost() {
+        return Err(Error::InvalidOptions(String::from(
+            "jump point search needs uniform cell costs",
+        )));
+    }
+    let mut state = SearchState::new(matrix);
+    let mut stats = SearchStats::default();
+
+    state.set_distance(start, 0);
+    state.open.push(Node {
+        position: *start,
+        distance: 0,
+        combined: heuristic.estimate(start, end),
+    });
+    stats.generated(state.open.len());
+
+    while let Some(current) = state.open.pop() {
+        if current.distance > state.get_distance(&current.position) {
+            continue;
+        }
+        if current.position == *end {
+            break;
+        }
+        state.close(&current.position);
+        stats.expanded(on_expand, &current.position, current.distance);
+        let parent = state.get_parent(&current.position);
+        for (d_y, d_x) in directions(matrix, &current.position, parent.as_ref()) {
+            if let Some(position) = jump(matrix, &current.position, d_y, d_x, end) {
+                let cost = current.distance
+                    + current.position.distance(&position) * matrix[&position].get_cost();
+                if cost < state.get_distance(&position) {
+                    state.set_distance(&position, cost);
+                    state.set_parent(&position, current.position);
+                    state.open.push(Node {
+                        position,
+                        distance: cost,
+                        combined: cost + heuristic.estimate(&position, end),
+                    });
+                    stats.generated(state.open.len());
+                }
+            }
+        }
+    }
+
+    let path = get_parents(&state, start, end).map(|jump_points| interpolate(&jump_points));
+    Ok(finish(path, stats, state, end))
+}
+
+/// The directions worth jumping in from `position`, given the jump point it
+/// was reached from: the natural neighbours plus any forced by walls.
+fn directions<T: Cell>(
+    matrix: &Matrix<T>,
+    position: &Position,
+    parent: Option<&Position>,
+) -> Vec<(i64, i64)> {
+    let parent = match parent {
+        Some(parent) => parent,
+        None => return DIRECTIONS.to_vec(),
+    };
+    let (y_pos, x_pos) = (position.y() as i64, position.x() as i64);
+    let d_y = (y_pos - parent.y() as i64).signum();
+    let d_x = (x_pos - parent.x() as i64).signum();
+    let free = |y_pos: i64, x_pos: i64| matrix.is_walkable(y_pos, x_pos);
+
+    let mut directions = Vec::new();
+    if d_y != 0 && d_x != 0 {
+        directions.extend_from_slice(&[(d_y, 0), (0, d_x), (d_y, d_x)]);
+        if !free(y_pos, x_pos - d_x) {
+            directions.push((d_y, -d_x));
+        }
+        if !free(y_pos - d_y, x_pos) {
+            directions.push((-d_y, d_x));
+        }
+    } else if d_x != 0 {
+        directions.push((0, d_x));
+        if !free(y_pos + 1, x_pos) {
+            directions.push((1, d_x));
+        }
+        if !free(y_pos - 1, x_pos) {
+            directions.push((-1, d_x));
+        }
+    } else {
+        directions.push((d_y, 0));
+        if !free(y_pos, x_pos + 1) {
+            directions.push((d_y, 1));
+        }
+        if !free(y_pos, x_pos - 1) {
+            directions.push((d_y, -1));
+        }
+    }
+    directions
+}
+
+/// Walks from `from` in one direction until it hits a wall, the end, or a
+/// cell with a forced neighbour, which becomes the next jump point.
+fn jump<T: Cell>(
+    matrix: &Matrix<T>,
+    from: &Position,
+    d_y: i64,
+    d_x: i64,
+    end: &Position,
+) -> Option<Position> {
+    let free = |y_pos: i64, x_pos: i64| matrix.is_walkable(y_pos, x_pos);
+    let (mut y_pos, mut x_pos) = (from.y() as i64, from.x() as i64);
+    loop {
+        y_pos += d_y;
+        x_pos += d_x;
+        if !free(y_pos, x_pos) {
+            return None;
+        }
+        let position = Position::new(y_pos as u32, x_pos as u32);
+        if position == *end {
+            return Some(position);
+        }
+        if d_y != 0 && d_x != 0 {
+            if (free(y_pos + d_y, x_pos - d_x) && !free(y_pos, x_pos - d_x))
+                || (free(y_pos - d_y, x_pos + d_x) && !free(y_pos - d_y, x_pos))
+            {
+                return Some(position);
+            }
+            if jump(matrix, &position, d_y, 0, end).is_some()
+                || jump(matrix, &position, 0, d_x, end).is_some()
+            {
+                return Some(position);
+            }
+        } else if d_x != 0 {
+            if (free(y_pos + 1, x_pos + d_x) && !free(y_pos + 1, x_pos))
+                || (free(y_pos - 1, x_pos + d_x) && !free(y_pos - 1, x_pos))
+            {
+                return Some(position);
+            }
+        } else if (free(y_pos + d_y, x_pos + 1) && !free(y_pos, x_pos + 1))
+            || (free(y_pos + d_y, x_pos - 1) && !free(y_pos, x_pos - 1))
+        {
+            return Some(position);
+        }
+    }
+}
+
+/// Fills in the cells between consecutive jump points, which always lie on
+/// a straight or diagonal line.
+fn interpolate(jump_points: &[Position]) -> Vec<Position> {
+    let mut path = Vec::new();
+    for pair in jump_points.windows(2) {
+        let (from, to) = (&pair[0], &pair[1]);
+        let d_y = (to.y() as i64 - from.y() as i64).signum();
+        let d_x = (to.x() as i64 - from.x() as i64).signum();
+        let (mut y_pos, mut x_pos) = (from.y() as i64, from.x() as i64);
+        for _ in 0..from.distance(to) {
+            path.push(Position::new(y_pos as u32, x_pos as u32));
+            y_pos += d_y;
+            x_pos += d_x;
+        }
+    }
+    path.extend(jump_points.last());
+    path
+}
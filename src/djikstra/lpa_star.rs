@@ -0,0 +1,49 @@
+use crate::backend::matrix::{CellUpdate, Matrix};
+use crate::cell::cell::{Cell, Position};
+use crate::error::Result;
+
+use super::incremental::{Direction, Planner};
+use super::options::SearchOptions;
+use super::SearchResult;
+
+/// Lifelong Planning A*: A* between a fixed start and goal that keeps its
+/// distances between calls. After a batch of edits only the cells whose
+/// distance from the start the edits changed are expanded again.
+///
+/// The planner owns its copy of the matrix. Report edits with `update` and
+/// call `plan` for the repaired path; the `nodes_expanded` of each plan
+/// counts only the expansions it had to redo.
+pub struct LifelongAStar<T: Cell> {
+    planner: Planner<T>,
+}
+
+impl<T: Cell> LifelongAStar<T> {
+    pub fn new(
+        matrix: Matrix<T>,
+        start: Position,
+        goal: Position,
+        options: SearchOptions,
+    ) -> Result<LifelongAStar<T>> {
+        Ok(LifelongAStar {
+            planner: Planner::new(matrix, start, goal, options, Direction::Forward)?,
+        })
+    }
+
+    pub fn matrix(&self) -> &Matrix<T> {
+        self.planner.matrix()
+    }
+
+    /// Applies a batch of edits to the matrix and marks the cells whose
+    /// distance from the start they may change. Nothing is searched until
+    /// the next `plan`.
+    pub fn update(&mut self, updates: &[CellUpdate]) -> Result<()> {
+        self.planner.update(updates)
+    }
+
+    /// Repairs the plan and gives the path from the start to the goal.
+    /// `stats` counts the expansions of this call only, and `state` marks
+    /// every cell whose distance from the start is known.
+    pub fn plan(&mut self) -> SearchResult {
+        self.planner.plan()
+    }
+}
@@ -1,77 +1,198 @@
-use super::backend::matrix::Matrix;
-use super::cell::{cell::Cell, cell::Position};
-use std::cmp;
+pub mod alternatives;
+pub mod ara;
+pub mod bidirectional;
+pub mod cbs;
+pub mod dstar_lite;
+pub mod heuristic;
+pub mod hpa;
+mod incremental;
+pub mod jps;
+pub mod lpa_star;
+pub mod options;
+pub mod result;
+pub mod state;
+pub mod stepper;
+pub mod theta;
+pub mod waypoints;
+
+use std::time::Instant;
 
-use std::collections::BinaryHeap;
+use super::backend::{connectivity::Connectivity, matrix::Matrix};
+use super::cell::{cell::Cell, cell::Position};
+use super::error::{Error, Result};
+pub use alternatives::Route;
+pub use ara::Budget;
+use heuristic::{Heuristic, Zero};
+pub use options::{Algorithm, SearchOptions};
+pub use result::{Expansion, ExpansionHook, SearchResult, SearchStats};
+use state::SearchState;
+pub use stepper::{Step, Stepper};
 
+/// Searches for the shortest path from `start` to `end` over `matrix` with
+/// the algorithm chosen in `options`. The matrix is only read, so any number
+/// of searches may share it.
+///
+/// Gives `Error::NoPath` straight away when the start or the end is a wall.
+/// An end that is free but cut off from the start gives a result without a
+/// path, whose state holds the region explored from the start.
 pub fn find_shortest<T: Cell>(
-    matrix: &mut Matrix<T>,
+    matrix: &Matrix<T>,
     start: &Position,
     end: &Position,
-) -> (Vec<T>, Matrix<T>) {
-    let mut start_pos = matrix[start].clone();
-    let mut pq = BinaryHeap::new();
+    options: &SearchOptions,
+) -> Result<SearchResult> {
+    let heuristic = checked_heuristic(options)?;
+    let connectivity = &options.connectivity;
+    check_ends(matrix, start, end)?;
+    let on_expand = options.on_expand.as_ref();
+    let timer = Instant::now();
+    let mut result = match options.algorithm {
+        Algorithm::AStar | Algorithm::Dijkstra => {
+            astar(matrix, start, end, heuristic, connectivity, 1.0, on_expand)
+        }
+        Algorithm::Weighted => astar(
+            matrix,
+            start,
+            end,
+            heuristic,
+            connectivity,
+            options.weight,
+            on_expand,
+        ),
+        Algorithm::Theta => theta::find_theta(matrix, start, end, connectivity, false, on_expand)?,
+        Algorithm::LazyTheta => {
+            theta::find_theta(matrix, start, end, connectivity, true, on_expand)?
+        }
+        Algorithm::Ara => ara::find_ara(matrix, start, end, options)?.1,
+        Algorithm::Jps => jps::find_jps(matrix, start, end, heuristic, connectivity, on_expand)?,
+        Algorithm::Bidirectional => bidirectional::find_bidirectional(
+            matrix,
+            start,
+            end,
+            heuristic,
+            connectivity,
+            on_expand,
+        ),
+    };
+    result.stats.elapsed = timer.elapsed();
+    Ok(result)
+}
 
-    start_pos.set_visited(true);
-    start_pos.set_distance(0);
-    pq.push(start_pos);
+/// A* with the heuristic scaled by `weight`. A weight above 1 trades path
+/// cost, by at most that factor, for fewer expansions.
+fn astar<T: Cell>(
+    matrix: &Matrix<T>,
+    start: &Position,
+    end: &Position,
+    heuristic: &dyn Heuristic,
+    connectivity: &Connectivity,
+    weight: f64,
+    on_expand: Option<&ExpansionHook>,
+) -> SearchResult {
+    Stepper::with_heuristic(
+        matrix,
+        start,
+        end,
+        heuristic,
+        connectivity,
+        weight,
+        on_expand,
+    )
+    .into_result()
+}
 
-    'outer: while !pq.is_empty() {
-        let current = pq.pop().expect("Something went wrong");
-        for each in matrix.get_neighbours(&current) {
-            match each {
-                Ok(neigbour) => {
-                    let cost = current.get_distance()
-                        + current.get_position().distance(&neigbour.get_position());
-                    if !neigbour.is_wall()
-                        && (!matrix[(neigbour.get_position())].get_visited()
-                            || cost < matrix[(neigbour.get_position())].get_distance())
-                    {
-                        let heuristic = heuristic(neigbour.get_position(), &end);
-                        matrix[(neigbour.get_position())].set_visited(true);
-                        matrix[(neigbour.get_position())].set_distance(cost);
-                        matrix[(neigbour.get_position())].set_heuristics(heuristic);
-                        matrix[(neigbour.get_position())].set_parent(current.clone());
-                        if neigbour.y() == end.y() && neigbour.x() == end.x() {
-                            break 'outer;
-                        }
-                        pq.push(matrix[(neigbour.get_position())].clone());
-                    }
-                }
-                Err(_) => {
-                    continue;
-                }
-            }
-        }
+/// The heuristic the algorithm of `options` searches with, once the options
+/// are known to fit together.
+pub(crate) fn checked_heuristic(options: &SearchOptions) -> Result<&dyn Heuristic> {
+    let heuristic = search_heuristic(options);
+    check_admissible(heuristic, &options.connectivity)?;
+    if !options.weight.is_finite() || options.weight < 1.0 {
+        return Err(Error::InvalidOptions(format!(
+            "weight must be at least 1, got {}",
+            options.weight
+        )));
     }
+    Ok(heuristic)
+}
 
-    let path = get_parents(matrix, &matrix[start].clone(), end);
+/// The heuristic the algorithm of `options` searches with.
+pub(crate) fn search_heuristic(options: &SearchOptions) -> &dyn Heuristic {
+    match options.algorithm {
+        // The any-angle searches measure straight-line distance themselves.
+        Algorithm::Dijkstra | Algorithm::Theta | Algorithm::LazyTheta => &Zero,
+        _ => options.heuristic.as_ref(),
+    }
+}
+
+/// Checks that `heuristic` never overestimates under `connectivity`.
+pub(crate) fn check_admissible(
+    heuristic: &dyn Heuristic,
+    connectivity: &Connectivity,
+) -> Result<()> {
+    if !heuristic.is_admissible(connectivity) {
+        return Err(Error::InvalidOptions(format!(
+            "heuristic is not admissible for {:?} connectivity",
+            connectivity
+        )));
+    }
+    Ok(())
+}
 
-    for each in &path {
-        matrix[each.get_position()].set_walk(true);
+/// Checks that `start` and `end` are free cells of `matrix`.
+fn check_ends<T: Cell>(matrix: &Matrix<T>, start: &Position, end: &Position) -> Result<()> {
+    for position in [start, end] {
+        if matrix.get(position)?.is_wall() {
+            return Err(Error::NoPath);
+        }
     }
-    (path, matrix.clone())
+    Ok(())
 }
 
-pub fn heuristic(start: &Position, end: &Position) -> u32 {
-    cmp::max(
-        i32::abs(start.i32x() - end.i32x()),
-        i32::abs(start.i32y() - end.i32y()),
-    ) as u32
+fn weighted(estimate: u32, weight: f64) -> u32 {
+    (estimate as f64 * weight) as u32
 }
 
-fn get_parents<T: Cell>(matrix: &mut Matrix<T>, start: &T, end: &Position) -> Vec<T> {
-    let mut path: Vec<T> = Vec::new();
-    let mut parent: (u32, u32) = (0, 0);
-    if let Some(parent_pos) = *matrix[(end)].get_parent() {
-        parent = parent_pos;
+fn finish(
+    path: Option<Vec<Position>>,
+    mut stats: SearchStats,
+    state: SearchState,
+    end: &Position,
+) -> SearchResult {
+    if let Some(path) = &path {
+        stats.path_cost = state.get_distance(end);
+        stats.path_length = path.len();
+        stats.euclidean_length = euclidean_length(path);
     }
-    let mut last: T = matrix[parent].clone();
-    while last.get_position() != start.get_position() {
-        path.push(last.clone());
-        if let Some(path) = last.get_parent() {
-            last = matrix[*path].clone();
+    SearchResult {
+        path,
+        waypoints: None,
+        stats,
+        state,
+        backward: None,
+        meeting: None,
+    }
+}
+
+/// Sum of the straight-line distances between consecutive points.
+fn euclidean_length(points: &[Position]) -> f64 {
+    points
+        .windows(2)
+        .map(|pair| pair[0].euclidean(&pair[1]))
+        .sum()
+}
+
+/// Follows the parents back from `end`. Gives `None` if the chain stops, or
+/// runs longer than there are cells, before reaching `start`.
+fn get_parents(state: &SearchState, start: &Position, end: &Position) -> Option<Vec<Position>> {
+    let mut path: Vec<Position> = vec![*end];
+    let mut last = *end;
+    while last != *start {
+        if path.len() > state.size() {
+            return None;
         }
+        last = state.get_parent(&last)?;
+        path.push(last);
     }
-    path
+    path.reverse();
+    Some(path)
 }
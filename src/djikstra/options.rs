@@ -0,0 +1,87 @@
+use std::str::FromStr;
+
+use crate::backend::connectivity::Connectivity;
+use crate::error::Error;
+
+use super::ara::Budget;
+use super::heuristic::{Chebyshev, Heuristic};
+use super::result::ExpansionHook;
+
+/// Which search `find_shortest` runs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Algorithm {
+    /// A* guided by the configured heuristic.
+    #[default]
+    AStar,
+    /// Plain Dijkstra; the configured heuristic is ignored.
+    Dijkstra,
+    /// Jump Point Search; needs 8-connectivity and uniform cell costs.
+    Jps,
+    /// A* from both ends at once, meeting in the middle.
+    Bidirectional,
+    /// A* with the heuristic scaled by the configured weight.
+    Weighted,
+    /// Anytime Repairing A*: weighted A* repeated with a shrinking weight
+    /// until the path is optimal or the budget runs out.
+    Ara,
+    /// Any-angle Theta*; needs uniform cell costs.
+    Theta,
+    /// Theta* that defers line-of-sight checks until a cell is expanded.
+    LazyTheta,
+}
+
+impl FromStr for Algorithm {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "astar" | "a*" => Ok(Algorithm::AStar),
+            "dijkstra" => Ok(Algorithm::Dijkstra),
+            "jps" => Ok(Algorithm::Jps),
+            "bidirectional" => Ok(Algorithm::Bidirectional),
+            "weighted" => Ok(Algorithm::Weighted),
+            "ara" | "ara*" => Ok(Algorithm::Ara),
+            "theta" | "theta*" => Ok(Algorithm::Theta),
+            "lazy-theta" | "lazy-theta*" => Ok(Algorithm::LazyTheta),
+            _ => Err(Error::InvalidOptions(format!(
+                "unknown algorithm '{}'",
+                name
+            ))),
+        }
+    }
+}
+
+/// Settings for a single search.
+pub struct SearchOptions {
+    pub heuristic: Box<dyn Heuristic + Send + Sync>,
+    pub connectivity: Connectivity,
+    pub algorithm: Algorithm,
+    /// Factor on the heuristic for the weighted and anytime searches.
+    pub weight: f64,
+    /// When the anytime search has to stop improving.
+    pub budget: Budget,
+    /// Called with every node the search expands, for tracing.
+    pub on_expand: Option<ExpansionHook>,
+}
+
+impl SearchOptions {
+    pub fn new(
+        heuristic: Box<dyn Heuristic + Send + Sync>,
+        connectivity: Connectivity,
+    ) -> SearchOptions {
+        SearchOptions {
+            heuristic,
+            connectivity,
+            algorithm: Algorithm::AStar,
+            weight: 1.0,
+            budget: Budget::Unlimited,
+            on_expand: None,
+        }
+    }
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        SearchOptions::new(Box::new(Chebyshev), Connectivity::Eight)
+    }
+}
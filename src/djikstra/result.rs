@@ -0,0 +1,127 @@
+use std::fmt::{self, Display, Formatter};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::cell::cell::Position;
+
+use super::state::SearchState;
+
+/// Called by a search with every node it expands, in order.
+pub type ExpansionHook = Arc<dyn Fn(&Expansion) + Send + Sync>;
+
+/// A node being expanded, as passed to an `ExpansionHook`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Expansion {
+    pub position: Position,
+    /// The best known cost of reaching the node.
+    pub distance: u32,
+    /// How many nodes this search has expanded, this one included.
+    pub number: usize,
+}
+
+/// Counters collected while searching. The path fields stay 0 when no path
+/// was found.
+#[derive(Clone, Debug, Default)]
+pub struct SearchStats {
+    pub nodes_expanded: usize,
+    /// Nodes put on the open list, the start included.
+    pub nodes_generated: usize,
+    /// The most nodes the open list held at once.
+    pub heap_peak: usize,
+    /// Times a node that had been expanded went back on the open list.
+    /// Only ARA*, LPA* and D* Lite ever do that; every other search leaves
+    /// this `None`.
+    pub reopenings: Option<usize>,
+    /// Line-of-sight checks made by the any-angle searches.
+    pub sight_checks: usize,
+    pub path_cost: u32,
+    pub path_length: usize,
+    /// Length of the path measured along straight lines between the centres
+    /// of its cells, or of its waypoints for any-angle searches.
+    pub euclidean_length: f64,
+    /// Wall-clock time the search took.
+    pub elapsed: Duration,
+}
+
+impl SearchStats {
+    /// Counts a node put on an open list that now holds `open` nodes.
+    pub(crate) fn generated(&mut self, open: usize) {
+        self.nodes_generated += 1;
+        self.heap_peak = usize::max(self.heap_peak, open);
+    }
+
+    /// Counts `count` nodes going back on the open list, and marks the
+    /// search as one that reopens nodes even when `count` is 0.
+    pub(crate) fn reopened(&mut self, count: usize) {
+        *self.reopenings.get_or_insert(0) += count;
+    }
+
+    /// Counts an expansion and reports it to `on_expand`.
+    pub(crate) fn expanded(
+        &mut self,
+        on_expand: Option<&ExpansionHook>,
+        position: &Position,
+        distance: u32,
+    ) {
+        self.nodes_expanded += 1;
+        if let Some(on_expand) = on_expand {
+            on_expand(&Expansion {
+                position: *position,
+                distance,
+                number: self.nodes_expanded,
+            });
+        }
+    }
+}
+
+impl Display for SearchStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "path length {}, cost {}, Euclidean length {:.2}, {} nodes expanded, {} generated, \
+             heap peak {}, ",
+            self.path_length,
+            self.path_cost,
+            self.euclidean_length,
+            self.nodes_expanded,
+            self.nodes_generated,
+            self.heap_peak
+        )?;
+        if let Some(reopenings) = self.reopenings {
+            write!(f, "{} reopenings, ", reopenings)?;
+        }
+        write!(f, "{:.3} ms", self.elapsed.as_secs_f64() * 1000.0)
+    }
+}
+
+/// The outcome of a search: the path from start to end, its statistics and
+/// the state the search finished in.
+///
+/// `path` is `None` when the end cannot be reached; `state` then holds the
+/// whole region that was explored from the start.
+///
+/// Searches that also run backwards from the end keep that half in
+/// `backward`, and the cell where both halves met in `meeting`.
+///
+/// Any-angle searches keep the corners of their path in `waypoints`; `path`
+/// then lists every cell the straight legs between them pass through.
+/// Routes through several stops keep the stops there, in the order visited.
+pub struct SearchResult {
+    pub path: Option<Vec<Position>>,
+    pub waypoints: Option<Vec<Position>>,
+    pub stats: SearchStats,
+    pub state: SearchState,
+    pub backward: Option<SearchState>,
+    pub meeting: Option<Position>,
+}
+
+impl SearchResult {
+    /// Whether either direction of the search reached the cell.
+    pub fn is_visited(&self, position: &Position) -> bool {
+        self.state.is_visited(position)
+            || self
+                .backward
+                .as_ref()
+                .is_some_and(|backward| backward.is_visited(position))
+    }
+}
@@ -0,0 +1,119 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::backend::matrix::Matrix;
+use crate::cell::cell::{Cell, Position};
+
+/// An entry of the open list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Node {
+    pub position: Position,
+    pub distance: u32,
+    pub combined: u32,
+}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Node {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .combined
+            .cmp(&self.combined)
+            .then_with(|| self.distance.cmp(&other.distance))
+    }
+}
+
+/// Per-search bookkeeping, kept apart from the grid so one `Matrix` can be
+/// searched any number of times.
+#[derive(Clone)]
+pub struct SearchState {
+    x_size: usize,
+    distance: Vec<u32>,
+    parent: Vec<Option<Position>>,
+    closed: Vec<bool>,
+    pub(crate) open: BinaryHeap<Node>,
+}
+
+impl SearchState {
+    pub fn new<T: Cell>(matrix: &Matrix<T>) -> SearchState {
+        let size = matrix.x_size() * matrix.y_size();
+        SearchState {
+            x_size: matrix.x_size(),
+            distance: vec![u32::MAX; size],
+            parent: vec![None; size],
+            closed: vec![false; size],
+            open: BinaryHeap::new(),
+        }
+    }
+
+    /// Number of cells tracked, one per cell of the matrix.
+    pub fn size(&self) -> usize {
+        self.distance.len()
+    }
+
+    fn index(&self, position: &Position) -> usize {
+        position.y() as usize * self.x_size + position.x() as usize
+    }
+
+    pub fn get_distance(&self, position: &Position) -> u32 {
+        self.distance[self.index(position)]
+    }
+
+    pub fn set_distance(&mut self, position: &Position, distance: u32) {
+        let index = self.index(position);
+        self.distance[index] = distance;
+    }
+
+    pub fn get_parent(&self, position: &Position) -> Option<Position> {
+        self.parent[self.index(position)]
+    }
+
+    pub fn set_parent(&mut self, position: &Position, parent: Position) {
+        let index = self.index(position);
+        self.parent[index] = Some(parent);
+    }
+
+    pub fn is_closed(&self, position: &Position) -> bool {
+        self.closed[self.index(position)]
+    }
+
+    pub fn close(&mut self, position: &Position) {
+        let index = self.index(position);
+        self.closed[index] = true;
+    }
+
+    /// Forgets which cells were expanded, so they may be expanded again.
+    pub fn reopen_all(&mut self) {
+        self.closed.iter_mut().for_each(|closed| *closed = false);
+    }
+
+    /// Takes in every cell another search over the same matrix reached,
+    /// keeping the lower of the two distances.
+    pub fn merge(&mut self, other: &SearchState) {
+        for (distance, other) in self.distance.iter_mut().zip(&other.distance) {
+            *distance = u32::min(*distance, *other);
+        }
+    }
+
+    /// The reached cells that are still waiting on the open list.
+    pub fn frontier(&self) -> Vec<Position> {
+        let mut frontier: Vec<Position> = self
+            .open
+            .iter()
+            .map(|node| node.position)
+            .filter(|position| !self.is_closed(position))
+            .collect();
+        frontier.sort_by_key(|position| (position.y(), position.x()));
+        frontier.dedup();
+        frontier
+    }
+
+    /// Whether the search has reached the cell at all.
+    pub fn is_visited(&self, position: &Position) -> bool {
+        self.get_distance(position) != u32::MAX
+    }
+}
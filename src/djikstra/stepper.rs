@@ -0,0 +1,216 @@
+use std::time::Instant;
+
+use crate::backend::{connectivity::Connectivity, matrix::Matrix};
+use crate::cell::cell::{Cell, Position};
+use crate::error::{Error, Result};
+
+use super::heuristic::Heuristic;
+use super::options::{Algorithm, SearchOptions};
+use super::state::{Node, SearchState};
+use super::{
+    check_ends, checked_heuristic, finish, get_parents, weighted, ExpansionHook, SearchResult,
+    SearchStats,
+};
+
+/// One expansion of a `Stepper`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Step {
+    /// The cell taken off the open list and expanded.
+    pub position: Position,
+    /// The cost of reaching it.
+    pub distance: u32,
+    /// The neighbours this expansion put on the open list, having found a
+    /// cheaper way to them.
+    pub pushed: Vec<Position>,
+    /// Entries on the open list afterwards, outdated ones included.
+    pub open: usize,
+}
+
+/// The A* loop of `find_shortest`, one expansion per call to `next`, so the
+/// search can be paused, inspected and drawn half way through. The
+/// iterator ends once the end is taken off the open list, or nothing is
+/// left to expand.
+pub struct Stepper<'a, T: Cell> {
+    matrix: &'a Matrix<T>,
+    start: Position,
+    end: Position,
+    heuristic: &'a dyn Heuristic,
+    connectivity: &'a Connectivity,
+    weight: f64,
+    on_expand: Option<&'a ExpansionHook>,
+    state: SearchState,
+    stats: SearchStats,
+    last: Option<Position>,
+    finished: bool,
+}
+
+impl<'a, T: Cell> Stepper<'a, T> {
+    /// Starts the search of `options`, which must be A*, Dijkstra or
+    /// weighted A*.
+    pub fn new(
+        matrix: &'a Matrix<T>,
+        start: &Position,
+        end: &Position,
+        options: &'a SearchOptions,
+    ) -> Result<Stepper<'a, T>> {
+        let weight = match options.algorithm {
+            Algorithm::AStar | Algorithm::Dijkstra => 1.0,
+            Algorithm::Weighted => options.weight,
+            algorithm => {
+                return Err(Error::InvalidOptions(format!(
+                    "{:?} cannot be run step by step",
+                    algorithm
+                )))
+            }
+        };
+        let heuristic = checked_heuristic(options)?;
+        check_ends(matrix, start, end)?;
+        Ok(Stepper::with_heuristic(
+            matrix,
+            start,
+            end,
+            heuristic,
+            &options.connectivity,
+            weight,
+            options.on_expand.as_ref(),
+        ))
+    }
+
+    /// Starts A* with the heuristic scaled by `weight`, without checking
+    /// the arguments.
+    pub(crate) fn with_heuristic(
+        matrix: &'a Matrix<T>,
+        start: &Position,
+        end: &Position,
+        heuristic: &'a dyn Heuristic,
+        connectivity: &'a Connectivity,
+        weight: f64,
+        on_expand: Option<&'a ExpansionHook>,
+    ) -> Stepper<'a, T> {
+        let mut state = SearchState::new(matrix);
+        let mut stats = SearchStats::default();
+        state.set_distance(start, 0);
+        state.open.push(Node {
+            position: *start,
+            distance: 0,
+            combined: weighted(heuristic.estimate(start, end), weight),
+        });
+        stats.generated(state.open.len());
+        Stepper {
+            matrix,
+            start: *start,
+            end: *end,
+            heuristic,
+            connectivity,
+            weight,
+            on_expand,
+            state,
+            stats,
+            last: None,
+            finished: false,
+        }
+    }
+
+    /// The distances, parents and open list so far.
+    pub fn state(&self) -> &SearchState {
+        &self.state
+    }
+
+    /// The counters so far; the path fields stay 0 until `into_result`, and
+    /// `elapsed` only counts the time spent stepping.
+    pub fn stats(&self) -> &SearchStats {
+        &self.stats
+    }
+
+    /// Whether the search has reached the end or run out of cells.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// The search so far as a result that `DataHandle::render` can draw.
+    /// Its path is that of `partial_path`.
+    pub fn snapshot(&self) -> SearchResult {
+        let last = self.partial_end();
+        let path = get_parents(&self.state, &self.start, &last);
+        finish(path, self.stats.clone(), self.state.clone(), &last)
+    }
+
+    /// The path to the cell expanded last, or to the end once that was
+    /// reached, without copying the state as `snapshot` does.
+    pub fn partial_path(&self) -> Option<Vec<Position>> {
+        get_parents(&self.state, &self.start, &self.partial_end())
+    }
+
+    fn partial_end(&self) -> Position {
+        if self.finished && self.state.is_visited(&self.end) {
+            self.end
+        } else {
+            self.last.unwrap_or(self.start)
+        }
+    }
+
+    /// Runs the rest of the search and gives its result.
+    pub fn into_result(mut self) -> SearchResult {
+        for _ in self.by_ref() {}
+        let path = get_parents(&self.state, &self.start, &self.end);
+        finish(path, self.stats, self.state, &self.end)
+    }
+
+    fn step(&mut self) -> Option<Step> {
+        if self.finished {
+            return None;
+        }
+        let current = loop {
+            match self.state.open.pop() {
+                Some(current) if current.distance > self.state.get_distance(&current.position) => {}
+                Some(current) if current.position != self.end => break current,
+                _ => {
+                    self.finished = true;
+                    return None;
+                }
+            }
+        };
+        let (matrix, state) = (self.matrix, &mut self.state);
+        state.close(&current.position);
+        self.stats
+            .expanded(self.on_expand, &current.position, current.distance);
+        self.last = Some(current.position);
+        let mut pushed = Vec::new();
+        for neigbour in matrix.get_neighbours(&matrix[&current.position], self.connectivity) {
+            let position = neigbour.get_position();
+            let cost = current.distance + current.position.distance(position) * neigbour.get_cost();
+            if !neigbour.is_wall()
+                && !state.is_closed(position)
+                && cost < state.get_distance(position)
+            {
+                state.set_distance(position, cost);
+                state.set_parent(position, current.position);
+                state.open.push(Node {
+                    position: *position,
+                    distance: cost,
+                    combined: cost
+                        + weighted(self.heuristic.estimate(position, &self.end), self.weight),
+                });
+                self.stats.generated(state.open.len());
+                pushed.push(*position);
+            }
+        }
+        Some(Step {
+            position: current.position,
+            distance: current.distance,
+            pushed,
+            open: state.open.len(),
+        })
+    }
+}
+
+impl<T: Cell> Iterator for Stepper<'_, T> {
+    type Item = Step;
+
+    fn next(&mut self) -> Option<Step> {
+        let timer = Instant::now();
+        let step = self.step();
+        self.stats.elapsed += timer.elapsed();
+        step
+    }
+}
@@ -0,0 +1,169 @@
+use crate::backend::{connectivity::Connectivity, matrix::Matrix};
+use crate::cell::cell::{Cell, Position};
+use crate::error::{Error, Result};
+
+use super::state::{Node, SearchState};
+use super::{euclidean_length, finish, get_parents, ExpansionHook, SearchResult, SearchStats};
+
+/// Distances are kept in thousandths of a cell, so legs of any length fit
+/// the integer keys of the open list.
+const SCALE: f64 = 1000.0;
+
+/// Theta*, or Lazy Theta* when `lazy` is set: A* that lets a cell take the
+/// parent of the cell it was reached from whenever the two can see each
+/// other, so the path runs in straight legs at any angle instead of
+/// following grid steps.
+///
+/// Lazy Theta* assumes every cell can see its parent's parent and only
+/// checks the line of sight once the cell is expanded, which saves most of
+/// the checks. Both need cells that all cost the same and ignore the
+/// configured heuristic in favour of the straight-line distance.
+pub fn find_theta<T: Cell>(
+    matrix: &Matrix<T>,
+    start: &Position,
+    end: &Position,
+    connectivity: &Connectivity,
+    lazy: bool,
+    on_expand: Option<&ExpansionHook>,
+) -> Result<SearchResult> {
+    if !matrix.is_uniform_cost() {
+        return Err(Error::InvalidOptions(String::from(
+            "any-angle search needs uniform cell costs",
+        )));
+    }
+    let mut state = SearchState::new(matrix);
+    let mut stats = SearchStats::default();
+
+    state.set_distance(start, 0);
+    state.open.push(Node {
+        position: *start,
+        distance: 0,
+        combined: length(start, end),
+    });
+    stats.generated(state.open.len());
+
+    while let Some(current) = state.open.pop() {
+        if current.distance > state.get_distance(&current.position)
+            || state.is_closed(&current.position)
+        {
+            continue;
+        }
+        if lazy {
+            set_vertex(
+                matrix,
+                &mut state,
+                &current.position,
+                connectivity,
+                &mut stats,
+            );
+        }
+        if current.position == *end {
+            break;
+        }
+        state.close(&current.position);
+        stats.expanded(
+            on_expand,
+            &current.position,
+            cells(current.distance, matrix[start].get_cost()),
+        );
+        let parent = state
+            .get_parent(&current.position)
+            .unwrap_or(current.position);
+        for neigbour in matrix.get_neighbours(&matrix[&current.position], connectivity) {
+            let position = neigbour.get_position();
+            if neigbour.is_wall() || state.is_closed(position) {
+                continue;
+            }
+            let from = if lazy || sees(matrix, &parent, position, connectivity, &mut stats) {
+                parent
+            } else {
+                current.position
+            };
+            let cost = state.get_distance(&from) + length(&from, position);
+            if cost < state.get_distance(position) {
+                state.set_distance(position, cost);
+                state.set_parent(position, from);
+                state.open.push(Node {
+                    position: *position,
+                    distance: cost,
+                    combined: cost + length(position, end),
+                });
+                stats.generated(state.open.len());
+            }
+        }
+    }
+
+    let waypoints = get_parents(&state, start, end);
+    let path = waypoints.as_ref().map(|waypoints| {
+        let mut path = vec![*start];
+        for leg in waypoints.windows(2) {
+            path.extend(leg[0].line_to(&leg[1]).into_iter().skip(1));
+        }
+        path
+    });
+    let mut result = finish(path, stats, state, end);
+    if let Some(waypoints) = &waypoints {
+        let distance = euclidean_length(waypoints);
+        result.stats.euclidean_length = distance;
+        result.stats.path_cost = (distance * matrix[start].get_cost() as f64).round() as u32;
+    }
+    result.waypoints = waypoints;
+    Ok(result)
+}
+
+/// A distance in units of `SCALE` back in whole cells of the given cost.
+fn cells(distance: u32, cost: u32) -> u32 {
+    (distance as f64 / SCALE * cost as f64).round() as u32
+}
+
+/// Straight-line distance between two cells in units of `SCALE`.
+fn length(from: &Position, to: &Position) -> u32 {
+    (from.euclidean(to) * SCALE).round() as u32
+}
+
+/// Checks the line of sight between two cells, counting the check.
+fn sees<T: Cell>(
+    matrix: &Matrix<T>,
+    from: &Position,
+    to: &Position,
+    connectivity: &Connectivity,
+    stats: &mut SearchStats,
+) -> bool {
+    stats.sight_checks += 1;
+    matrix.line_of_sight(from, to, connectivity)
+}
+
+/// Lazy Theta*'s check of the assumed parent of a cell about to be
+/// expanded. If the cell cannot see it, the cell is hooked onto the expanded
+/// neighbour that reaches it most cheaply instead.
+fn set_vertex<T: Cell>(
+    matrix: &Matrix<T>,
+    state: &mut SearchState,
+    position: &Position,
+    connectivity: &Connectivity,
+    stats: &mut SearchStats,
+) {
+    let parent = match state.get_parent(position) {
+        Some(parent) => parent,
+        None => return,
+    };
+    if sees(matrix, &parent, position, connectivity, stats) {
+        return;
+    }
+    let best = matrix
+        .get_neighbours(&matrix[position], connectivity)
+        .iter()
+        .map(|neigbour| *neigbour.get_position())
+        .filter(|neigbour| state.is_closed(neigbour))
+        .map(|neigbour| {
+            (
+                state.get_distance(&neigbour) + length(&neigbour, position),
+                neigbour,
+            )
+        })
+        .min_by_key(|(cost, _)| *cost);
+    if let Some((cost, neigbour)) = best {
+        state.set_distance(position, cost);
+        state.set_parent(position, neigbour);
+    }
+}
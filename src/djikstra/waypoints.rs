@@ -0,0 +1,343 @@
+use std::str::FromStr;
+use std::time::Instant;
+
+use crate::backend::matrix::Matrix;
+use crate::cell::cell::{Cell, Position};
+use crate::error::{Error, Result};
+
+use super::heuristic::Heuristic;
+use super::options::SearchOptions;
+use super::state::{Node, SearchState};
+use super::{
+    check_ends, checked_heuristic, euclidean_length, find_shortest, finish, get_parents,
+    SearchResult, SearchStats,
+};
+
+/// Most stops `Order::Optimal` accepts. Finding the best order takes time
+/// exponential in the number of stops.
+pub const MAX_OPTIMAL_STOPS: usize = 10;
+
+/// The order in which `find_route` visits its stops.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Order {
+    /// As given.
+    #[default]
+    Given,
+    /// Whichever order makes the whole route cheapest.
+    Optimal,
+}
+
+impl FromStr for Order {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        match name {
+            "given" => Ok(Order::Given),
+            "optimal" => Ok(Order::Optimal),
+            _ => Err(Error::InvalidOptions(format!("unknown order '{}'", name))),
+        }
+    }
+}
+
+/// Searches from `start` for whichever of `goals` is cheapest to reach. The
+/// heuristic of `options`, or none for Dijkstra, is used towards the nearest
+/// goal; any other algorithm searches as A*. Gives `Error::NoPath` if the start or any goal is a wall.
+pub fn find_nearest<T: Cell>(
+    matrix: &Matrix<T>,
+    start: &Position,
+    goals: &[Position],
+    options: &SearchOptions,
+) -> Result<SearchResult> {
+    let connectivity = &options.connectivity;
+    let heuristic = checked_heuristic(options)?;
+    if goals.is_empty() {
+        return Err(Error::InvalidOptions(String::from(
+            "no goals to search for",
+        )));
+    }
+    for goal in goals {
+        check_ends(matrix, start, goal)?;
+    }
+    let heuristic = Nearest { heuristic, goals };
+    let on_expand = options.on_expand.as_ref();
+    let timer = Instant::now();
+    let mut state = SearchState::new(matrix);
+    let mut stats = SearchStats::default();
+    let mut reached = None;
+
+    state.set_distance(start, 0);
+    state.open.push(Node {
+        position: *start,
+        distance: 0,
+        combined: heuristic.estimate(start),
+    });
+    stats.generated(state.open.len());
+
+    while let Some(current) = state.open.pop() {
+        if current.distance > state.get_distance(&current.position) {
+            continue;
+        }
+        if goals.contains(&current.position) {
+            reached = Some(current.position);
+            break;
+        }
+        state.close(&current.position);
+        stats.expanded(on_expand, &current.position, current.distance);
+        for neigbour in matrix.get_neighbours(&matrix[&current.position], connectivity) {
+            let position = neigbour.get_position();
+            let cost = current.distance + current.position.distance(position) * neigbour.get_cost();
+            if !neigbour.is_wall()
+                && !state.is_closed(position)
+                && cost < state.get_distance(position)
+            {
+                state.set_distance(position, cost);
+                state.set_parent(position, current.position);
+                state.open.push(Node {
+                    position: *position,
+                    distance: cost,
+                    combined: cost + heuristic.estimate(position),
+                });
+                stats.generated(state.open.len());
+            }
+        }
+    }
+
+    let goal = reached.unwrap_or(goals[0]);
+    let path = reached.and_then(|goal| get_parents(&state, start, &goal));
+    let mut result = finish(path, stats, state, &goal);
+    result.stats.elapsed = timer.elapsed();
+    Ok(result)
+}
+
+/// The route from `start` through every one of `stops` to `end`, built from
+/// one search per leg with the algorithm of `options`. `waypoints` of the
+/// result lists the start, the stops in the order visited and the end, and
+/// `state` every cell any leg reached.
+///
+/// `Order::Optimal` searches every pair of points once and picks the
+/// cheapest order with the Held-Karp algorithm; it accepts at most
+/// `MAX_OPTIMAL_STOPS` stops. If some stop cannot be reached the result has
+/// no path.
+pub fn find_route<T: Cell>(
+    matrix: &Matrix<T>,
+    start: &Position,
+    stops: &[Position],
+    end: &Position,
+    options: &SearchOptions,
+    order: Order,
+) -> Result<SearchResult> {
+    let timer = Instant::now();
+    let mut points = vec![*start];
+    points.extend_from_slice(stops);
+    points.push(*end);
+    let mut legs = Legs::new(points.len());
+    let visits = match order {
+        Order::Given => {
+            for index in 1..points.len() {
+                legs.search(matrix, &points, index - 1, index, options)?;
+            }
+            Some((0..points.len()).collect())
+        }
+        Order::Optimal => {
+            if stops.len() > MAX_OPTIMAL_STOPS {
+                return Err(Error::InvalidOptions(format!(
+                    "at most {} stops can be ordered, got {}",
+                    MAX_OPTIMAL_STOPS,
+                    stops.len()
+                )));
+            }
+            for from in 0..points.len() {
+                for to in from + 1..points.len() {
+                    legs.search(matrix, &points, from, to, options)?;
+                }
+            }
+            let costs = |from, to| legs.cost(matrix, from, to);
+            cheapest_order(&costs, points.len())
+        }
+    };
+
+    let mut state = SearchState::new(matrix);
+    let mut stats = SearchStats::default();
+    for result in &legs.results {
+        state.merge(&result.state);
+        stats.nodes_expanded += result.stats.nodes_expanded;
+        stats.nodes_generated += result.stats.nodes_generated;
+        stats.heap_peak = usize::max(stats.heap_peak, result.stats.heap_peak);
+        if let Some(reopenings) = result.stats.reopenings {
+            stats.reopened(reopenings);
+        }
+    }
+    let mut path: Option<Vec<Position>> = None;
+    let mut waypoints = None;
+    if let Some(visits) = visits {
+        let mut route = vec![*start];
+        for pair in visits.windows(2) {
+            match legs.path(matrix, pair[0], pair[1]) {
+                Some((leg, cost)) => {
+                    route.extend_from_slice(&leg[1..]);
+                    stats.path_cost += cost;
+                }
+                None => {
+                    route.clear();
+                    break;
+                }
+            }
+        }
+        if !route.is_empty() {
+            stats.path_length = route.len();
+            stats.euclidean_length = euclidean_length(&route);
+            waypoints = Some(visits.iter().map(|index| points[*index]).collect());
+            path = Some(route);
+        }
+    }
+    if path.is_none() {
+        stats.path_cost = 0;
+    }
+    stats.elapsed = timer.elapsed();
+    Ok(SearchResult {
+        path,
+        waypoints,
+        stats,
+        state,
+        backward: None,
+        meeting: None,
+    })
+}
+
+/// The estimate to whichever goal is closest, which never overestimates
+/// when the wrapped heuristic does not.
+struct Nearest<'a> {
+    heuristic: &'a dyn Heuristic,
+    goals: &'a [Position],
+}
+
+impl Nearest<'_> {
+    fn estimate(&self, from: &Position) -> u32 {
+        self.goals
+            .iter()
+            .map(|goal| self.heuristic.estimate(from, goal))
+            .min()
+            .unwrap_or(0)
+    }
+}
+
+/// The searched legs between pairs of points, each searched in one
+/// direction only.
+struct Legs {
+    count: usize,
+    /// Index into `results` for each ordered pair that was searched.
+    searched: Vec<Option<usize>>,
+    results: Vec<SearchResult>,
+}
+
+impl Legs {
+    fn new(count: usize) -> Legs {
+        Legs {
+            count,
+            searched: vec![None; count * count],
+            results: Vec::new(),
+        }
+    }
+
+    fn search<T: Cell>(
+        &mut self,
+        matrix: &Matrix<T>,
+        points: &[Position],
+        from: usize,
+        to: usize,
+        options: &SearchOptions,
+    ) -> Result<()> {
+        let result = find_shortest(matrix, &points[from], &points[to], options)?;
+        self.searched[from * self.count + to] = Some(self.results.len());
+        self.results.push(result);
+        Ok(())
+    }
+
+    /// The path and cost from one point to another.
+    fn path<T: Cell>(
+        &self,
+        matrix: &Matrix<T>,
+        from: usize,
+        to: usize,
+    ) -> Option<(Vec<Position>, u32)> {
+        let cost = self.cost(matrix, from, to)?;
+        let path = match self.searched[from * self.count + to] {
+            Some(index) => self.results[index].path.clone()?,
+            None => {
+                let index = self.searched[to * self.count + from]?;
+                let mut path = self.results[index].path.clone()?;
+                path.reverse();
+                path
+            }
+        };
+        Some((path, cost))
+    }
+
+    /// The cost from one point to another. A leg searched the other way
+    /// round is walked backwards, which enters the start of that search
+    /// instead of its end; only those two cell costs differ.
+    fn cost<T: Cell>(&self, matrix: &Matrix<T>, from: usize, to: usize) -> Option<u32> {
+        if let Some(index) = self.searched[from * self.count + to] {
+            let result = &self.results[index];
+            return result.path.as_ref().map(|_| result.stats.path_cost);
+        }
+        let result = &self.results[self.searched[to * self.count + from]?];
+        let path = result.path.as_ref()?;
+        Some(
+            result.stats.path_cost + matrix[&path[0]].get_cost()
+                - matrix[&path[path.len() - 1]].get_cost(),
+        )
+    }
+}
+
+/// Held-Karp: the cheapest order to visit every point between the first and
+/// the last, as indices into the points, or `None` if no order connects.
+fn cheapest_order(costs: &dyn Fn(usize, usize) -> Option<u32>, count: usize) -> Option<Vec<usize>> {
+    let stops = count - 2;
+    if stops == 0 {
+        costs(0, 1)?;
+        return Some(vec![0, 1]);
+    }
+    // best[set][last]: cheapest way from the start through the stops in
+    // `set` ending at stop `last`, and the stop before it.
+    let mut best: Vec<Vec<Option<(u32, usize)>>> = vec![vec![None; stops]; 1 << stops];
+    for last in 0..stops {
+        best[1 << last][last] = costs(0, last + 1).map(|cost| (cost, usize::MAX));
+    }
+    for set in 1..(1usize << stops) {
+        for last in 0..stops {
+            let (cost, _) = match best[set][last] {
+                Some(entry) if set & (1 << last) != 0 => entry,
+                _ => continue,
+            };
+            for next in (0..stops).filter(|next| set & (1 << next) == 0) {
+                let step = match costs(last + 1, next + 1) {
+                    Some(step) => step,
+                    None => continue,
+                };
+                let entry = &mut best[set | (1 << next)][next];
+                if entry.is_none_or(|(known, _)| cost + step < known) {
+                    *entry = Some((cost + step, last));
+                }
+            }
+        }
+    }
+    let full = (1 << stops) - 1;
+    let (_, mut last) = (0..stops)
+        .filter_map(|last| {
+            let (cost, _) = best[full][last]?;
+            Some((cost + costs(last + 1, count - 1)?, last))
+        })
+        .min()?;
+    let mut order = vec![count - 1];
+    let mut set = full;
+    while last != usize::MAX {
+        order.push(last + 1);
+        let (_, previous) = best[set][last]?;
+        set &= !(1 << last);
+        last = previous;
+    }
+    order.push(0);
+    order.reverse();
+    Some(order)
+}
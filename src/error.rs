@@ -0,0 +1,71 @@
+use std::fmt::{self, Display, Formatter};
+use std::io;
+
+use image::ImageError;
+
+use crate::cell::cell::Position;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The file exists but could not be decoded as an image.
+    ImageDecode(ImageError),
+    /// No file at the given path.
+    FileNotFound(String),
+    /// Reading or writing a file failed.
+    Io(io::Error),
+    /// The image has no red start marker.
+    NoStartMarker,
+    /// The image has no blue end marker.
+    NoEndMarker,
+    /// The image has no black walls to crop the maze to.
+    MazeBoundsNotFound,
+    /// The end cannot be reached from the start.
+    NoPath,
+    /// A position lies outside the matrix.
+    OutOfBounds(Position),
+    /// The search options cannot be used together.
+    InvalidOptions(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::ImageDecode(error) => write!(f, "Could not decode image: {}", error),
+            Error::FileNotFound(path) => write!(f, "No such file: {}", path),
+            Error::Io(error) => write!(f, "{}", error),
+            Error::NoStartMarker => write!(f, "No red start marker found in the maze"),
+            Error::NoEndMarker => write!(f, "No blue end marker found in the maze"),
+            Error::MazeBoundsNotFound => write!(f, "No walls found to bound the maze"),
+            Error::NoPath => write!(f, "No path exists between start and end"),
+            Error::OutOfBounds(position) => write!(f, "Position {}is outside the maze", position),
+            Error::InvalidOptions(reason) => write!(f, "Invalid search options: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::ImageDecode(error) => Some(error),
+            Error::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<ImageError> for Error {
+    fn from(error: ImageError) -> Self {
+        match error {
+            ImageError::IoError(error) => Error::Io(error),
+            error => Error::ImageDecode(error),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
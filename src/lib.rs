@@ -0,0 +1,45 @@
+//! Shortest paths through mazes drawn in images.
+//!
+//! A maze is an image with black walls, a red start marker and a blue end
+//! marker. Load one with [`backend::from_path`], [`backend::from_memory`] or
+//! [`backend::get_data`], locate the markers with
+//! [`DataHandle::get_start`] and [`DataHandle::get_end`], then search it with
+//! [`find_shortest`] and draw the result with [`DataHandle::render`], or as
+//! an SVG overlay with [`DataHandle::render_svg`]. [`DataHandle::to_json`],
+//! [`DataHandle::path_csv`] and [`DataHandle::summary_csv`] export the
+//! solution for other tools.
+//! [`batch::solve_directory`] does all of that for a whole directory.
+//! A [`Stepper`] runs the same A* one expansion at a time, to pause and draw
+//! the search half way through; [`DataHandle::render_search`] turns that
+//! into the frames of an animation.
+//!
+//! When the maze changes after the first plan, [`DStarLite`] and
+//! [`LifelongAStar`] repair the path instead of searching again from scratch.
+//! For very large mazes, a [`Hierarchy`] of clusters answers queries quickly
+//! once built; it is kept and queried from the library, and is not one of
+//! the algorithms of [`find_shortest`] or the command line.
+//!
+//! For more than one route, [`djikstra::alternatives`] finds the k shortest
+//! or a set of routes that overlap little, drawn with
+//! [`DataHandle::render_routes`]. [`djikstra::waypoints`] searches for the
+//! nearest of several goals, or routes through waypoint markers.
+//!
+//! [`djikstra::cbs`] moves several agents at once without collisions.
+//!
+//! The `djikstra` binary reads its command line with [`cli::parse`].
+
+pub mod backend;
+pub mod batch;
+pub mod cell;
+pub mod cli;
+pub mod djikstra;
+pub mod error;
+
+pub use backend::{connectivity::Connectivity, matrix::CellUpdate, terrain::Terrain, DataHandle};
+pub use cell::{cell::Cell, cell::Position, MyCell};
+pub use djikstra::{
+    dstar_lite::DStarLite, find_shortest, hpa::Hierarchy, lpa_star::LifelongAStar, Algorithm,
+    Budget, Expansion, ExpansionHook, Route, SearchOptions, SearchResult, SearchStats, Step,
+    Stepper,
+};
+pub use error::{Error, Result};
@@ -1,56 +1,213 @@
-mod backend;
-mod cell;
-mod djikstra;
+use std::{env, fs, io, process};
 
-use std::io;
-
-use backend::file_handler::FileHandler;
-use cell::{cell::Position, MyCell};
-
-const RESULTS: &str = "./resources/results/";
-const IMAGES: &str = "./resources/images/";
+use djikstra::backend::{
+    self,
+    file_handler::{self, FileHandler, GifWriter},
+};
+use djikstra::batch;
+use djikstra::cli::{self, Agents, Batch, Command, Format, Routes, Solve, Stops, IMAGES, RESULTS};
+use djikstra::djikstra::{alternatives, ara, cbs, waypoints};
+use djikstra::{find_shortest, Algorithm, Error, MyCell, Result, SearchOptions, Stepper, Terrain};
 
 fn main() {
-    if let Ok(_) = run() {};
+    let result = cli::parse(env::args().skip(1).collect()).and_then(|command| match command {
+        Command::Interactive => run(),
+        Command::Solve(solve) => solve_file(&solve),
+        Command::Batch(batch) => solve_batch(&batch),
+        Command::Agents(agents) => move_agents(&agents),
+        Command::Help => {
+            println!("{}", cli::USAGE);
+            Ok(())
+        }
+    });
+    if let Err(error) = result {
+        eprintln!("{}", error);
+        if let Error::InvalidOptions(_) = error {
+            eprintln!("{}", cli::USAGE);
+        }
+        process::exit(1);
+    }
 }
 
-fn run() -> io::Result<()> {
+fn run() -> Result<()> {
     let file_handler = FileHandler::new(String::from(RESULTS), String::from(IMAGES));
     let mut buffer;
     let stdin = io::stdin();
-    if let Ok(values) = file_handler.read_directory() {
-        for value in values {
-            if let Some(name) = value?.path().file_name() {
-                println!("{:?}", name);
-            }
+    let options = SearchOptions::default();
+    for value in file_handler.read_directory()? {
+        if let Some(name) = value?.path().file_name() {
+            println!("{:?}", name);
         }
     }
 
     println!("Choose a file");
     loop {
         buffer = String::new();
-        if let Ok(_response) = stdin.read_line(&mut buffer) {
-            if !(buffer == String::from("quit") || buffer == String::from("q")) {
-                if let Ok(mut index) =
-                    backend::get_data::<MyCell>(&file_handler, String::from(buffer.trim()))
-                {
-                    let start = index.get_start();
-                    let end = index.get_end();
-                    assert!(start != Position { position: (0, 0) });
-                    assert!(end != Position { position: (0, 0) });
-                    println!("{}", start);
-                    println!("{}", end);
-                    let (path, matrix) = djikstra::find_shortest(&mut index.matrix(), &start, &end);
-                    index.write_image(&path, &matrix);
-                    println!("{}", path.len());
-                } else {
-                    println!("No such file!");
-                };
-            } else {
-                break;
+        if stdin.read_line(&mut buffer)? == 0 || buffer.trim() == "quit" || buffer.trim() == "q" {
+            break;
+        }
+        if let Err(error) = solve(&file_handler, buffer.trim(), &options) {
+            println!("{}", error);
+        }
+        println!("Choose a new file")
+    }
+    Ok(())
+}
+
+fn solve(file_handler: &FileHandler, name: &str, options: &SearchOptions) -> Result<()> {
+    let index = backend::get_data::<MyCell>(file_handler, String::from(name), &Terrain::Uniform)?;
+    let start = index.get_start()?;
+    let end = index.get_end()?;
+    println!("{}", start);
+    println!("{}", end);
+    let result = find_shortest(index.matrix(), &start, &end, options)?;
+    file_handler.write_image(&index.render(&result), name)?;
+    println!("File saved");
+    match result.path {
+        Some(_) => println!("{}", result.stats),
+        None => println!("{}", Error::NoPath),
+    }
+    Ok(())
+}
+
+fn solve_file(solve: &Solve) -> Result<()> {
+    let index = backend::from_path::<MyCell>(&solve.input, &solve.terrain)?;
+    let start = match solve.start {
+        Some((x_pos, y_pos)) => index.to_matrix(x_pos, y_pos)?,
+        None => index.get_start()?,
+    };
+    let ends = match (solve.end, solve.stops) {
+        (Some((x_pos, y_pos)), _) => vec![index.to_matrix(x_pos, y_pos)?],
+        (None, Stops::Nearest) => index.get_ends()?,
+        (None, _) => vec![index.get_end()?],
+    };
+    let end = ends[0];
+    if let Some(routes) = solve.routes {
+        let routes = match routes {
+            Routes::Shortest(k) => {
+                alternatives::k_shortest(index.matrix(), &start, &end, &solve.options, k)?
             }
-            println!("Choose a new file")
+            Routes::Diverse(k, penalty) => alternatives::diverse_routes(
+                index.matrix(),
+                &start,
+                &end,
+                &solve.options,
+                k,
+                penalty,
+            )?,
         };
+        if routes.is_empty() {
+            return Err(Error::NoPath);
+        }
+        index.render_routes(&routes).save(&solve.output)?;
+        for (number, route) in routes.iter().enumerate() {
+            println!(
+                "{}: route {}, length {}, cost {}",
+                index.name(),
+                number + 1,
+                route.path.len(),
+                route.cost
+            );
+        }
+        println!("written to {}", solve.output.display());
+        return Ok(());
     }
+    let result = match solve.stops {
+        Stops::Direct if solve.format == Format::Gif => {
+            let stepper = Stepper::new(index.matrix(), &start, &end, &solve.options)?;
+            let mut gif = GifWriter::create(&solve.output, solve.delay)?;
+            index.render_search(stepper, solve.frame_step, |frame| gif.write(frame))?
+        }
+        Stops::Direct if solve.options.algorithm == Algorithm::Ara => {
+            let (solutions, result) = ara::find_ara(index.matrix(), &start, &end, &solve.options)?;
+            for solution in &solutions {
+                println!(
+                    "{}: weight {}, cost {}, at most {:.3} times the shortest, {} nodes expanded",
+                    index.name(),
+                    solution.weight,
+                    solution.cost,
+                    solution.bound,
+                    solution.nodes_expanded
+                );
+            }
+            result
+        }
+        Stops::Direct => find_shortest(index.matrix(), &start, &end, &solve.options)?,
+        Stops::Nearest => waypoints::find_nearest(index.matrix(), &start, &ends, &solve.options)?,
+        Stops::Waypoints(order) => {
+            let stops = match &solve.waypoints {
+                Some(points) => points
+                    .iter()
+                    .map(|&(x_pos, y_pos)| index.to_matrix(x_pos, y_pos))
+                    .collect::<Result<Vec<_>>>()?,
+                None => index.get_waypoints(),
+            };
+            waypoints::find_route(index.matrix(), &start, &stops, &end, &solve.options, order)?
+        }
+    };
+    match solve.format {
+        Format::Png => index.render(&result).save(&solve.output)?,
+        Format::Text => fs::write(&solve.output, index.render_text(&result))?,
+        Format::Gif => {}
+        Format::Svg => fs::write(
+            &solve.output,
+            index.render_svg(&result, &start, &end, &solve.svg)?,
+        )?,
+    }
+    let exports = &solve.exports;
+    if let Some(json) = &exports.json {
+        fs::write(json, index.to_json(&result, &start, &end))?;
+    }
+    if let Some(csv) = &exports.csv {
+        fs::write(csv, index.path_csv(&result))?;
+    }
+    if let Some(summary) = &exports.summary_csv {
+        fs::write(summary, index.summary_csv(&result, &start, &end))?;
+    }
+    if result.path.is_none() {
+        return Err(Error::NoPath);
+    }
+    println!(
+        "{}: {}, written to {}",
+        index.name(),
+        result.stats,
+        solve.output.display()
+    );
+    if let Some(waypoints) = &result.waypoints {
+        println!("{} waypoints", waypoints.len());
+    }
+    Ok(())
+}
+
+fn solve_batch(args: &Batch) -> Result<()> {
+    let file_handler = FileHandler::new(args.results.clone(), args.images.clone());
+    let entries = batch::solve_directory::<MyCell>(
+        &file_handler,
+        &args.terrain,
+        &args.options,
+        args.workers,
+    )?;
+    print!("{}", batch::report_table(&entries));
+    if let Some(report) = &args.report {
+        fs::write(report, batch::report_csv(&entries))?;
+    }
+    Ok(())
+}
+
+fn move_agents(args: &Agents) -> Result<()> {
+    let index = backend::from_path::<MyCell>(&args.input, &Terrain::Uniform)?;
+    let agents = index.get_agents()?;
+    let plan = cbs::find_plan(index.matrix(), &agents, &args.connectivity, args.max_nodes)?;
+    let frames = index.render_agents(&plan, args.frame_step);
+    file_handler::save_gif(&args.output, frames, args.delay)?;
+    println!(
+        "{}: {} agents, sum of costs {}, makespan {}, {} constraint tree nodes, written to {}",
+        index.name(),
+        agents.len(),
+        plan.cost,
+        plan.makespan,
+        plan.nodes_expanded,
+        args.output.display()
+    );
     Ok(())
 }
@@ -0,0 +1,75 @@
+mod common;
+
+use std::collections::HashSet;
+
+use djikstra::backend::matrix::Matrix;
+use djikstra::djikstra::alternatives::{diverse_routes, k_shortest};
+use djikstra::{Cell, CellUpdate, MyCell, Position, Route, SearchOptions};
+
+use common::{assert_walkable, fresh_cost, room};
+
+fn assert_valid(matrix: &Matrix<MyCell>, route: &Route) {
+    let cells: HashSet<&Position> = route.path.iter().collect();
+    assert_eq!(cells.len(), route.path.len(), "route visits a cell twice");
+    assert_walkable(matrix, &route.path);
+    let mut cost = 0;
+    for step in route.path.windows(2) {
+        cost += matrix[&step[1]].get_cost();
+    }
+    assert_eq!(cost, route.cost);
+}
+
+#[test]
+fn k_shortest_gives_distinct_routes_in_order_of_cost() {
+    let matrix = room(16);
+    let start = Position::new(7, 2);
+    let goal = Position::new(7, 11);
+
+    let routes = k_shortest(&matrix, &start, &goal, &SearchOptions::default(), 6).unwrap();
+    assert_eq!(routes.len(), 6);
+    assert_eq!(Some(routes[0].cost), fresh_cost(&matrix, &start, &goal));
+    for pair in routes.windows(2) {
+        assert!(pair[0].cost <= pair[1].cost);
+    }
+    for (index, route) in routes.iter().enumerate() {
+        assert_eq!(route.path.first(), Some(&start));
+        assert_eq!(route.path.last(), Some(&goal));
+        assert_valid(&matrix, route);
+        assert!(!routes[..index].contains(route));
+    }
+}
+
+#[test]
+fn diverse_routes_take_different_gaps() {
+    let mut matrix = room(30);
+    let rows = matrix.y_size() as u32;
+    // A wall with one gap on the straight line and one well off it.
+    for y_pos in (0..rows).filter(|y_pos| *y_pos != 14 && *y_pos != 4) {
+        matrix
+            .apply(&CellUpdate::Wall(Position::new(y_pos, 14)))
+            .unwrap();
+    }
+    let start = Position::new(14, 2);
+    let goal = Position::new(14, 25);
+
+    let routes = diverse_routes(&matrix, &start, &goal, &SearchOptions::default(), 2, 1.0).unwrap();
+    assert_eq!(routes.len(), 2);
+    assert_eq!(Some(routes[0].cost), fresh_cost(&matrix, &start, &goal));
+    assert!(routes[1].path.contains(&Position::new(4, 14)));
+    let first: HashSet<&Position> = routes[0].path.iter().collect();
+    let shared = routes[1]
+        .path
+        .iter()
+        .filter(|cell| first.contains(cell))
+        .count();
+    assert!(shared < routes[1].path.len() / 4, "{} cells shared", shared);
+    for route in &routes {
+        assert_valid(&matrix, route);
+    }
+
+    // The k shortest all squeeze through the nearer gap.
+    let shortest = k_shortest(&matrix, &start, &goal, &SearchOptions::default(), 2).unwrap();
+    assert!(shortest[1].path.contains(&Position::new(14, 14)));
+
+    assert!(diverse_routes(&matrix, &start, &goal, &SearchOptions::default(), 2, 0.0).is_err());
+}
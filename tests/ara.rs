@@ -0,0 +1,105 @@
+mod common;
+
+use std::path::Path;
+use std::time::Duration;
+
+use djikstra::backend;
+use djikstra::djikstra::ara::find_ara;
+use djikstra::{Algorithm, Budget, MyCell, Position, SearchOptions, Terrain};
+
+use common::{fresh_cost, options, room};
+
+fn budgeted(budget: Budget) -> SearchOptions {
+    SearchOptions {
+        weight: 3.0,
+        budget,
+        ..options(Algorithm::Ara)
+    }
+}
+
+#[test]
+fn ara_improves_its_path_until_it_is_optimal() {
+    let index = backend::from_path::<MyCell>(
+        Path::new("./resources/images/squaremed2.jpg"),
+        &Terrain::Uniform,
+    )
+    .unwrap();
+    let start = index.get_start().unwrap();
+    let end = index.get_end().unwrap();
+    let (solutions, result) =
+        find_ara(index.matrix(), &start, &end, &budgeted(Budget::Unlimited)).unwrap();
+
+    assert!(solutions.len() > 1);
+    assert_eq!(solutions[0].weight, 3.0);
+    for pair in solutions.windows(2) {
+        assert!(pair[1].weight < pair[0].weight);
+        assert!(pair[1].bound <= pair[0].bound);
+        assert!(pair[1].cost <= pair[0].cost);
+        assert!(pair[1].nodes_expanded >= pair[0].nodes_expanded);
+    }
+    for solution in &solutions {
+        assert!(solution.bound >= 1.0 && solution.bound <= solution.weight);
+    }
+    let last = &solutions[solutions.len() - 1];
+    assert_eq!(last.bound, 1.0);
+    assert_eq!(Some(last.cost), fresh_cost(index.matrix(), &start, &end));
+    assert_eq!(result.stats.path_cost, last.cost);
+    assert_eq!(result.path.as_ref(), Some(&last.path));
+}
+
+#[test]
+fn ara_keeps_to_its_budget_from_the_first_pass() {
+    let index = backend::from_path::<MyCell>(
+        Path::new("./resources/images/squaremed2.jpg"),
+        &Terrain::Uniform,
+    )
+    .unwrap();
+    let start = index.get_start().unwrap();
+    let end = index.get_end().unwrap();
+    let (all, _) = find_ara(index.matrix(), &start, &end, &budgeted(Budget::Unlimited)).unwrap();
+
+    // Spent before the first path is found: nothing to hand back.
+    let budget = all[0].nodes_expanded / 2;
+    let (solutions, result) = find_ara(
+        index.matrix(),
+        &start,
+        &end,
+        &budgeted(Budget::Expansions(budget)),
+    )
+    .unwrap();
+    assert!(solutions.is_empty());
+    assert!(result.path.is_none());
+    assert_eq!(result.stats.nodes_expanded, budget);
+
+    // Spent half way through the passes: the best path so far.
+    let budget = (all[1].nodes_expanded + all[all.len() - 1].nodes_expanded) / 2;
+    let (solutions, result) = find_ara(
+        index.matrix(),
+        &start,
+        &end,
+        &budgeted(Budget::Expansions(budget)),
+    )
+    .unwrap();
+    assert!(!solutions.is_empty() && solutions.len() < all.len());
+    assert!(result.stats.nodes_expanded <= budget);
+    for (solution, unlimited) in solutions.iter().zip(&all) {
+        assert!(solution.nodes_expanded <= budget);
+        assert_eq!(solution.cost, unlimited.cost);
+    }
+    let best = &solutions[solutions.len() - 1];
+    assert_eq!(result.stats.path_cost, best.cost);
+    assert_eq!(result.path.as_ref(), Some(&best.path));
+
+    // A time budget that is already spent stops the first pass too.
+    let matrix = room(40);
+    let (start, end) = (Position::new(2, 2), Position::new(37, 37));
+    let (solutions, result) = find_ara(
+        &matrix,
+        &start,
+        &end,
+        &budgeted(Budget::Time(Duration::ZERO)),
+    )
+    .unwrap();
+    assert!(solutions.is_empty());
+    assert_eq!(result.stats.nodes_expanded, 0);
+}
@@ -0,0 +1,154 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use image::{GenericImageView, Rgb, RgbImage};
+
+use djikstra::backend::file_handler::FileHandler;
+use djikstra::batch::{report_csv, report_table, solve_directory, BatchEntry};
+use djikstra::{Error, MyCell, SearchOptions, Terrain};
+
+const MAZES: [&str; 3] = ["tubemed12", "squareeasy1", "squaremed7"];
+
+/// A scratch directory of mazes, with one blank image that is no maze.
+fn images(name: &str) -> PathBuf {
+    let directory = env::temp_dir().join(format!("djikstra-{}-{}", name, std::process::id()));
+    let images = directory.join("images");
+    fs::create_dir_all(&images).unwrap();
+    for maze in MAZES {
+        fs::copy(
+            format!("./resources/images/{}.jpg", maze),
+            images.join(format!("{}.jpg", maze)),
+        )
+        .unwrap();
+    }
+    RgbImage::from_pixel(8, 8, Rgb([255, 255, 255]))
+        .save(images.join("blank.png"))
+        .unwrap();
+    directory
+}
+
+fn solve(directory: &Path, workers: usize) -> Vec<BatchEntry> {
+    let file_handler = FileHandler::new(
+        format!("{}/results/", directory.display()),
+        format!("{}/images/", directory.display()),
+    );
+    solve_directory::<MyCell>(
+        &file_handler,
+        &Terrain::Uniform,
+        &SearchOptions::default(),
+        workers,
+    )
+    .unwrap()
+}
+
+#[test]
+fn entries_come_back_in_name_order_whatever_the_workers() {
+    let directory = images("order");
+    let single = solve(&directory, 1);
+    let names: Vec<&str> = single.iter().map(|entry| entry.name.as_str()).collect();
+    assert_eq!(names, ["blank", "squareeasy1", "squaremed7", "tubemed12"]);
+    assert!(matches!(single[0].result, Err(Error::MazeBoundsNotFound)));
+
+    for workers in [3, 8] {
+        let pooled = solve(&directory, workers);
+        assert_eq!(pooled.len(), single.len());
+        for (entry, expected) in pooled.iter().zip(&single) {
+            assert_eq!(entry.name, expected.name);
+            match (&entry.result, &expected.result) {
+                (Ok(solution), Ok(expected)) => {
+                    assert_eq!(solution.stats.path_cost, expected.stats.path_cost);
+                    assert_eq!(solution.stats.nodes_expanded, expected.stats.nodes_expanded);
+                }
+                (Err(error), Err(expected)) => {
+                    assert_eq!(error.to_string(), expected.to_string())
+                }
+                _ => panic!("{} differs with {} workers", entry.name, workers),
+            }
+        }
+    }
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn each_maze_is_written_under_its_own_name() {
+    let directory = images("results");
+    let entries = solve(&directory, 1);
+    assert_eq!(entries.len(), MAZES.len() + 1);
+    let mut written: Vec<String> = fs::read_dir(directory.join("results"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    written.sort();
+    assert_eq!(
+        written,
+        ["squareeasy1.png", "squaremed7.png", "tubemed12.png"]
+    );
+    for maze in MAZES {
+        let solved = image::open(directory.join(format!("results/{}.png", maze))).unwrap();
+        let source = image::open(directory.join(format!("images/{}.jpg", maze))).unwrap();
+        assert_eq!(solved.dimensions(), source.dimensions());
+    }
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn reports_list_every_entry() {
+    let directory = images("report");
+    let entries = solve(&directory, 1);
+    fs::remove_dir_all(&directory).unwrap();
+
+    let csv = report_csv(&entries);
+    let mut lines = csv.lines();
+    let header = lines.next().unwrap();
+    assert_eq!(
+        header,
+        "maze,path_length,path_cost,nodes_expanded,nodes_generated,heap_peak,reopenings,runtime_ms,error"
+    );
+    let rows: Vec<&str> = lines.collect();
+    assert_eq!(rows.len(), entries.len());
+    assert_eq!(
+        rows[0],
+        format!("blank,,,,,,,,\"{}\"", Error::MazeBoundsNotFound)
+    );
+    for (row, entry) in rows.iter().zip(&entries).skip(1) {
+        let fields: Vec<&str> = row.split(',').collect();
+        assert_eq!(fields.len(), header.split(',').count());
+        let stats = &entry.result.as_ref().unwrap().stats;
+        assert_eq!(fields[0], entry.name);
+        assert_eq!(fields[2], stats.path_cost.to_string());
+        assert_eq!(fields[3], stats.nodes_expanded.to_string());
+        assert_eq!(fields[6], "");
+        assert!(fields[7].parse::<f64>().is_ok());
+        assert_eq!(fields[8], "");
+    }
+
+    let table = report_table(&entries);
+    let lines: Vec<&str> = table.lines().collect();
+    assert_eq!(lines.len(), entries.len() + 1);
+    let columns: Vec<&str> = lines[0].split("  ").map(str::trim).collect();
+    assert_eq!(
+        columns
+            .into_iter()
+            .filter(|column| !column.is_empty())
+            .collect::<Vec<_>>(),
+        [
+            "maze",
+            "length",
+            "cost",
+            "expanded",
+            "generated",
+            "runtime (ms)"
+        ]
+    );
+    // The name column is as wide as the longest name.
+    assert!(lines[0].starts_with(&format!("{:<11}  ", "maze")));
+    assert_eq!(
+        lines[1],
+        format!("{:<11}  {}", "blank", Error::MazeBoundsNotFound)
+    );
+    for (line, entry) in lines[2..].iter().zip(&entries[1..]) {
+        assert!(line.starts_with(&entry.name));
+        assert_eq!(line.len(), lines[0].len());
+    }
+}
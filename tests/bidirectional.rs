@@ -0,0 +1,59 @@
+mod common;
+
+use djikstra::backend;
+use djikstra::{find_shortest, Algorithm, CellUpdate, MyCell, Position, Terrain};
+
+use common::{bundled_images, cost, fresh_cost, options, room, solve};
+
+#[test]
+fn bidirectional_matches_astar_on_every_bundled_image() {
+    for path in bundled_images() {
+        let index = backend::from_path::<MyCell>(&path, &Terrain::Uniform).unwrap();
+        let start = index.get_start().unwrap();
+        let end = index.get_end().unwrap();
+        let result = solve(&index, Algorithm::Bidirectional);
+
+        assert_eq!(
+            cost(&result),
+            fresh_cost(index.matrix(), &start, &end),
+            "{} costs differ",
+            index.name()
+        );
+        let path = result.path.as_ref().unwrap();
+        assert_eq!(path.first(), Some(&start));
+        assert_eq!(path.last(), Some(&end));
+        let meeting = result.meeting.expect("no meeting cell");
+        assert!(path.contains(&meeting), "{}", index.name());
+        assert!(result.state.is_visited(&meeting));
+        assert!(result.backward.as_ref().unwrap().is_visited(&meeting));
+    }
+}
+
+#[test]
+fn bidirectional_gives_no_path_to_an_unreachable_goal() {
+    let mut matrix = room(20);
+    for y_pos in 0..20 {
+        matrix
+            .apply(&CellUpdate::Wall(Position::new(y_pos, 10)))
+            .unwrap();
+    }
+    let start = Position::new(5, 3);
+    let end = Position::new(15, 16);
+    let result = find_shortest(&matrix, &start, &end, &options(Algorithm::Bidirectional)).unwrap();
+    assert!(result.path.is_none());
+    assert!(result.meeting.is_none());
+    assert_eq!(result.stats.path_cost, 0);
+
+    // Through a gap in the wall, the halves meet on the path.
+    matrix
+        .apply(&CellUpdate::Free(Position::new(12, 10)))
+        .unwrap();
+    let result = find_shortest(&matrix, &start, &end, &options(Algorithm::Bidirectional)).unwrap();
+    let path = result.path.unwrap();
+    assert!(path.contains(&result.meeting.unwrap()));
+    assert!(path.contains(&Position::new(12, 10)));
+    assert_eq!(
+        Some(result.stats.path_cost),
+        fresh_cost(&matrix, &start, &end)
+    );
+}
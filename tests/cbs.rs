@@ -0,0 +1,94 @@
+mod common;
+
+use djikstra::djikstra::cbs::{find_plan, Agent, DEFAULT_MAX_NODES};
+use djikstra::{Cell, CellUpdate, Connectivity, Position};
+
+use common::{fresh_cost, room};
+
+#[test]
+fn agents_swapping_ends_of_a_corridor_make_way_for_each_other() {
+    let mut matrix = room(14);
+    // One corridor along row 6 with a single alcove above it.
+    for y_pos in 0..matrix.y_size() as u32 {
+        for x_pos in 0..matrix.x_size() as u32 {
+            if y_pos != 6 && (y_pos, x_pos) != (5, 6) {
+                matrix
+                    .apply(&CellUpdate::Wall(Position::new(y_pos, x_pos)))
+                    .unwrap();
+            }
+        }
+    }
+    let agents = [
+        Agent {
+            start: Position::new(6, 1),
+            goal: Position::new(6, 11),
+        },
+        Agent {
+            start: Position::new(6, 11),
+            goal: Position::new(6, 1),
+        },
+    ];
+
+    let plan = find_plan(&matrix, &agents, &Connectivity::Four, DEFAULT_MAX_NODES).unwrap();
+    assert_eq!(plan.first_conflict(), None);
+    assert!(plan.nodes_expanded > 1);
+    for (path, agent) in plan.paths.iter().zip(&agents) {
+        assert_eq!(path.first(), Some(&agent.start));
+        assert_eq!(path.last(), Some(&agent.goal));
+        for step in path.windows(2) {
+            assert!(step[0].distance(&step[1]) <= 1);
+            assert!(!matrix[&step[1]].is_wall());
+        }
+    }
+    let alcove = Position::new(5, 6);
+    assert!(plan.paths.iter().any(|path| path.contains(&alcove)));
+    // Ten steps each, plus the detour into the alcove and the wait there.
+    assert!(plan.cost > 20);
+    assert_eq!(
+        plan.cost,
+        plan.paths.iter().map(|path| path.len() - 1).sum::<usize>()
+    );
+}
+
+#[test]
+fn crossing_agents_in_the_open_keep_their_shortest_times() {
+    let matrix = room(20);
+    let agents = [
+        Agent {
+            start: Position::new(2, 2),
+            goal: Position::new(17, 17),
+        },
+        Agent {
+            start: Position::new(17, 2),
+            goal: Position::new(2, 17),
+        },
+        Agent {
+            start: Position::new(10, 2),
+            goal: Position::new(10, 17),
+        },
+    ];
+
+    let plan = find_plan(&matrix, &agents, &Connectivity::Eight, DEFAULT_MAX_NODES).unwrap();
+    assert_eq!(plan.first_conflict(), None);
+    // Uniform cells, so each agent still arrives as soon as it could alone.
+    let alone: u32 = agents
+        .iter()
+        .map(|agent| fresh_cost(&matrix, &agent.start, &agent.goal).unwrap())
+        .sum();
+    assert_eq!(plan.cost, alone as usize);
+    assert_eq!(plan.makespan, 15);
+    for time in 0..=plan.makespan {
+        assert_ne!(plan.position(0, time), plan.position(1, time));
+        assert_ne!(plan.position(0, time), plan.position(2, time));
+        assert_ne!(plan.position(1, time), plan.position(2, time));
+    }
+
+    let shared = [
+        agents[0],
+        Agent {
+            start: Position::new(5, 5),
+            goal: agents[0].goal,
+        },
+    ];
+    assert!(find_plan(&matrix, &shared, &Connectivity::Eight, DEFAULT_MAX_NODES).is_err());
+}
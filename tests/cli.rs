@@ -0,0 +1,160 @@
+use std::path::PathBuf;
+
+use djikstra::cli::{self, Command, Format, Routes, Stops};
+use djikstra::djikstra::waypoints::Order;
+use djikstra::{Algorithm, Connectivity, Error, Result};
+
+fn parse(args: &str) -> Result<Command> {
+    cli::parse(args.split_whitespace().map(String::from).collect())
+}
+
+/// The reason `args` is refused for.
+fn rejected(args: &str) -> String {
+    match parse(args) {
+        Err(Error::InvalidOptions(reason)) => reason,
+        Err(error) => panic!("{}: {}", args, error),
+        Ok(_) => panic!("{} was accepted", args),
+    }
+}
+
+#[test]
+fn solve_reads_its_flags() {
+    let solve = match parse(
+        "solve mazes/maze.jpg -o out.svg --format svg --algorithm jps --connectivity 4 \
+         --start 3,4 --end 10,12",
+    ) {
+        Ok(Command::Solve(solve)) => solve,
+        _ => panic!("not a solve command"),
+    };
+    assert_eq!(solve.input, PathBuf::from("mazes/maze.jpg"));
+    assert_eq!(solve.output, PathBuf::from("out.svg"));
+    assert_eq!(solve.format, Format::Svg);
+    assert_eq!(solve.options.algorithm, Algorithm::Jps);
+    assert_eq!(solve.options.connectivity, Connectivity::Four);
+    assert_eq!(solve.start, Some((3, 4)));
+    assert_eq!(solve.end, Some((10, 12)));
+    assert_eq!(solve.stops, Stops::Direct);
+    assert_eq!(solve.waypoints, None);
+    assert_eq!(solve.routes, None);
+
+    match parse("solve mazes/maze.jpg --diverse 3 --overlap-penalty 2.5") {
+        Ok(Command::Solve(solve)) => {
+            assert_eq!(solve.output, PathBuf::from("maze-solved.png"));
+            assert_eq!(solve.routes, Some(Routes::Diverse(3, 2.5)));
+        }
+        _ => panic!("not a solve command"),
+    }
+    match parse("solve mazes/maze.jpg --stops in-order --waypoints 30,4:2,17:9,9") {
+        Ok(Command::Solve(solve)) => {
+            assert_eq!(solve.stops, Stops::Waypoints(Order::Given));
+            assert_eq!(solve.waypoints, Some(vec![(30, 4), (2, 17), (9, 9)]));
+        }
+        _ => panic!("not a solve command"),
+    }
+    assert!(matches!(parse("--help"), Ok(Command::Help)));
+    assert!(matches!(parse(""), Ok(Command::Interactive)));
+}
+
+#[test]
+fn batch_reads_its_directories_and_report() {
+    match parse("batch mazes -o solved --report report.csv -j 3 --algorithm jps") {
+        Ok(Command::Batch(batch)) => {
+            assert_eq!(batch.images, "mazes/");
+            assert_eq!(batch.results, "solved/");
+            assert_eq!(batch.report, Some(PathBuf::from("report.csv")));
+            assert_eq!(batch.workers, 3);
+            assert_eq!(batch.options.algorithm, Algorithm::Jps);
+        }
+        _ => panic!("not a batch command"),
+    }
+    match parse("batch") {
+        Ok(Command::Batch(batch)) => {
+            assert_eq!(batch.images, cli::IMAGES);
+            assert_eq!(batch.results, cli::RESULTS);
+            assert_eq!(batch.report, None);
+            assert!(batch.workers >= 1);
+        }
+        _ => panic!("not a batch command"),
+    }
+}
+
+#[test]
+fn unknown_flags_and_missing_values_are_refused() {
+    assert_eq!(
+        rejected("solve m.png --colour red"),
+        "unknown option '--colour'"
+    );
+    assert_eq!(rejected("solve m.png --format"), "--format needs a value");
+    assert_eq!(rejected("solve m.png --format bmp"), "unknown format 'bmp'");
+    assert_eq!(
+        rejected("solve m.png --algorithm hpa"),
+        "unknown algorithm 'hpa'"
+    );
+    assert_eq!(
+        rejected("solve m.png --weight heavy"),
+        "--weight expects a number but got 'heavy'"
+    );
+    assert_eq!(
+        rejected("solve m.png --start 3"),
+        "--start expects x,y but got '3'"
+    );
+    assert_eq!(
+        rejected("solve m.png --stops optimal --waypoints 3,4:5"),
+        "--waypoints expects x,y but got '5'"
+    );
+    assert_eq!(rejected("solve"), "solve needs an input");
+    assert_eq!(rejected("agents"), "agents needs an input");
+    assert_eq!(rejected("frobnicate m.png"), "unknown command 'frobnicate'");
+    assert_eq!(
+        rejected("batch -j 0"),
+        "--jobs expects a positive number but got '0'"
+    );
+}
+
+#[test]
+fn conflicting_flags_are_refused() {
+    for args in [
+        "solve m.png --stops nearest --k-shortest 3",
+        "solve m.png --stops in-order --diverse 2",
+    ] {
+        assert_eq!(
+            rejected(args),
+            "--stops cannot be combined with --k-shortest or --diverse"
+        );
+    }
+    for args in [
+        "solve m.png --k-shortest 3 --json out.json",
+        "solve m.png --diverse 2 --summary-csv out.csv",
+    ] {
+        assert_eq!(rejected(args), "several routes cannot be exported");
+    }
+    assert_eq!(
+        rejected("solve m.png --stops nearest --format gif"),
+        "--stops cannot be animated"
+    );
+    assert_eq!(
+        rejected("solve m.png --diverse 3 --format gif"),
+        "several routes can only be drawn as png"
+    );
+    assert_eq!(
+        rejected("solve m.png --k-shortest 2 --diverse 2"),
+        "--k-shortest and --diverse cannot be combined"
+    );
+    for args in [
+        "solve m.png --waypoints 3,4",
+        "solve m.png --stops nearest --waypoints 3,4",
+    ] {
+        assert_eq!(
+            rejected(args),
+            "--waypoints needs --stops in-order or optimal"
+        );
+    }
+    assert_eq!(
+        rejected("solve m.png --overlap-penalty 2"),
+        "--overlap-penalty needs --diverse"
+    );
+    assert_eq!(
+        rejected("solve m.png --time-budget 5 --expansion-budget 5"),
+        "--time-budget and --expansion-budget cannot be combined"
+    );
+}
@@ -0,0 +1,83 @@
+// Each test crate compiles this module but uses only some of its helpers.
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::PathBuf;
+
+use image::{DynamicImage, Rgb, RgbImage};
+
+use djikstra::backend::{self, matrix::Matrix};
+use djikstra::{
+    find_shortest, Algorithm, Cell, DataHandle, MyCell, Position, SearchOptions, SearchResult,
+    Terrain,
+};
+
+/// An open room surrounded by a black frame.
+pub fn room(size: u32) -> Matrix<MyCell> {
+    let mut image = RgbImage::from_pixel(size, size, Rgb([255, 255, 255]));
+    for index in 0..size {
+        image.put_pixel(index, 0, Rgb([0, 0, 0]));
+        image.put_pixel(index, size - 1, Rgb([0, 0, 0]));
+        image.put_pixel(0, index, Rgb([0, 0, 0]));
+        image.put_pixel(size - 1, index, Rgb([0, 0, 0]));
+    }
+    let index = backend::from_image::<MyCell>(
+        String::from("room"),
+        DynamicImage::ImageRgb8(image),
+        &Terrain::Uniform,
+    )
+    .unwrap();
+    index.matrix().clone()
+}
+
+/// Every image in the resources folder, in name order.
+pub fn bundled_images() -> Vec<PathBuf> {
+    let mut paths: Vec<_> = fs::read_dir("./resources/images/")
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty());
+    paths
+}
+
+/// The default options with another algorithm.
+pub fn options(algorithm: Algorithm) -> SearchOptions {
+    SearchOptions {
+        algorithm,
+        ..SearchOptions::default()
+    }
+}
+
+/// Searches a loaded maze from its start marker to its end marker.
+pub fn solve(index: &DataHandle<MyCell>, algorithm: Algorithm) -> SearchResult {
+    let start = index.get_start().unwrap();
+    let end = index.get_end().unwrap();
+    find_shortest(index.matrix(), &start, &end, &options(algorithm)).unwrap()
+}
+
+/// The cost of the path a search found, if it found one.
+pub fn cost(result: &SearchResult) -> Option<u32> {
+    result.path.as_ref().map(|_| result.stats.path_cost)
+}
+
+/// The cost A* finds from scratch, if there is a path at all.
+pub fn fresh_cost(matrix: &Matrix<MyCell>, start: &Position, goal: &Position) -> Option<u32> {
+    cost(&find_shortest(matrix, start, goal, &SearchOptions::default()).unwrap())
+}
+
+/// Fails unless the path moves one cell at a time and never enters a wall.
+pub fn assert_walkable(matrix: &Matrix<MyCell>, path: &[Position]) {
+    for pair in path.windows(2) {
+        assert_eq!(
+            pair[0].distance(&pair[1]),
+            1,
+            "{:?} skips to {:?}",
+            pair[0],
+            pair[1]
+        );
+    }
+    for position in path {
+        assert!(!matrix[position].is_wall(), "{:?} is a wall", position);
+    }
+}
@@ -0,0 +1,121 @@
+mod common;
+
+use std::collections::HashSet;
+
+use djikstra::backend::matrix::Matrix;
+use djikstra::{Cell, CellUpdate, Connectivity, Error, MyCell, Position};
+
+use common::{fresh_cost, room};
+
+const ALL: [Connectivity; 4] = [
+    Connectivity::Four,
+    Connectivity::Eight,
+    Connectivity::EightNoCornerCut,
+    Connectivity::EightStrict,
+];
+
+/// The cells `get_neighbours` gives around `(y, x)`, walls included.
+fn neighbours(
+    matrix: &Matrix<MyCell>,
+    y_pos: u32,
+    x_pos: u32,
+    connectivity: Connectivity,
+) -> HashSet<Position> {
+    matrix
+        .get_neighbours(&matrix[(y_pos, x_pos)], &connectivity)
+        .iter()
+        .map(|cell| *cell.get_position())
+        .collect()
+}
+
+fn positions(cells: &[(u32, u32)]) -> HashSet<Position> {
+    cells
+        .iter()
+        .map(|(y_pos, x_pos)| Position::new(*y_pos, *x_pos))
+        .collect()
+}
+
+#[test]
+fn diagonals_past_a_wall_corner_depend_on_the_connectivity() {
+    // Two walls touching at a corner, below and to the right of (3, 3).
+    let mut matrix = room(8);
+    matrix
+        .apply(&CellUpdate::Wall(Position::new(4, 3)))
+        .unwrap();
+    matrix
+        .apply(&CellUpdate::Wall(Position::new(3, 4)))
+        .unwrap();
+
+    let orthogonal = [(4, 3), (3, 2), (3, 4), (2, 3)];
+    assert_eq!(
+        neighbours(&matrix, 3, 3, Connectivity::Four),
+        positions(&orthogonal)
+    );
+    let mut expected = positions(&orthogonal);
+    expected.extend(positions(&[(2, 2), (2, 4), (4, 2), (4, 4)]));
+    assert_eq!(neighbours(&matrix, 3, 3, Connectivity::Eight), expected);
+    // Squeezing between the two walls is the one step a corner cut needs.
+    expected.remove(&Position::new(4, 4));
+    assert_eq!(
+        neighbours(&matrix, 3, 3, Connectivity::EightNoCornerCut),
+        expected
+    );
+    // Strict diagonals need both cells beside the step free.
+    expected.remove(&Position::new(4, 2));
+    expected.remove(&Position::new(2, 4));
+    assert_eq!(
+        neighbours(&matrix, 3, 3, Connectivity::EightStrict),
+        expected
+    );
+
+    assert_eq!(
+        fresh_cost(&matrix, &Position::new(3, 3), &Position::new(4, 4)),
+        Some(1)
+    );
+}
+
+#[test]
+fn neighbours_stop_at_the_edges_of_the_matrix() {
+    let matrix = room(8);
+    let (last_y, last_x) = (matrix.y_size() as u32 - 1, matrix.x_size() as u32 - 1);
+
+    // In a corner, between the two walls of the room's border.
+    let counts: Vec<usize> = ALL
+        .iter()
+        .map(|connectivity| neighbours(&matrix, 0, 0, *connectivity).len())
+        .collect();
+    assert_eq!(counts, [2, 3, 2, 2]);
+    assert_eq!(
+        neighbours(&matrix, last_y, last_x, Connectivity::Eight),
+        positions(&[
+            (last_y - 1, last_x),
+            (last_y, last_x - 1),
+            (last_y - 1, last_x - 1)
+        ])
+    );
+
+    // Along the top edge, next to free cells below.
+    let counts: Vec<usize> = ALL
+        .iter()
+        .map(|connectivity| neighbours(&matrix, 0, 4, *connectivity).len())
+        .collect();
+    assert_eq!(counts, [3, 5, 5, 3]);
+    assert!(neighbours(&matrix, 0, 4, Connectivity::Eight)
+        .iter()
+        .all(|position| position.y() <= 1));
+}
+
+#[test]
+fn connectivity_names_parse() {
+    let names = ["4", "8", "8-no-corner-cut", "8-strict"];
+    for (name, connectivity) in names.iter().zip(ALL) {
+        assert_eq!(name.parse::<Connectivity>().unwrap(), connectivity);
+    }
+    for name in ["", "6", "eight", "8-STRICT", " 8", "8-no-corner"] {
+        assert!(
+            matches!(name.parse::<Connectivity>(), Err(Error::InvalidOptions(_))),
+            "{:?}",
+            name
+        );
+    }
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use djikstra::{Cell, CellUpdate, DStarLite, Position, SearchOptions};
+
+use common::{cost, fresh_cost, room};
+
+#[test]
+fn dstar_lite_repairs_to_the_cost_of_a_fresh_search() {
+    let matrix = room(40);
+    let start = Position::new(20, 2);
+    let goal = Position::new(20, 35);
+    let mut planner =
+        DStarLite::new(matrix.clone(), start, goal, SearchOptions::default()).unwrap();
+    let rows = matrix.y_size() as u32;
+    let mut expected = matrix;
+
+    let first = planner.plan();
+    assert_eq!(cost(&first), fresh_cost(&expected, &start, &goal));
+
+    // A wall across the room with a gap near the top.
+    let wall: Vec<CellUpdate> = (0..rows)
+        .filter(|y_pos| *y_pos != 2)
+        .map(|y_pos| CellUpdate::Wall(Position::new(y_pos, 18)))
+        .collect();
+    for update in &wall {
+        expected.apply(update).unwrap();
+    }
+    planner.update(&wall).unwrap();
+    let detour = planner.plan();
+    let path = detour.path.clone().unwrap();
+    assert_eq!(
+        Some(detour.stats.path_cost),
+        fresh_cost(&expected, &start, &goal)
+    );
+    assert!(path.iter().all(|position| !expected[position].is_wall()));
+    assert!(detour.stats.nodes_expanded > 0);
+
+    // Walk part of the way, then close the gap and open another one.
+    let moved = path[5];
+    planner.move_to(moved).unwrap();
+    let doors = [
+        CellUpdate::Wall(Position::new(2, 18)),
+        CellUpdate::Free(Position::new(30, 18)),
+    ];
+    for update in &doors {
+        expected.apply(update).unwrap();
+    }
+    planner.update(&doors).unwrap();
+    let replanned = planner.plan();
+    assert_eq!(replanned.path.as_ref().unwrap()[0], moved);
+    assert_eq!(
+        Some(replanned.stats.path_cost),
+        fresh_cost(&expected, &moved, &goal)
+    );
+
+    // Sealing the last gap leaves no way through.
+    planner
+        .update(&[CellUpdate::Wall(Position::new(30, 18))])
+        .unwrap();
+    assert!(planner.plan().path.is_none());
+}
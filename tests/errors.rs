@@ -0,0 +1,78 @@
+use std::path::Path;
+
+use image::{DynamicImage, Rgb, RgbImage};
+
+use djikstra::backend::{self, DataHandle};
+use djikstra::{find_shortest, Algorithm, Error, MyCell, Position, Result, SearchOptions, Terrain};
+
+/// A walled room with the given marker pixels drawn in.
+fn maze(markers: &[(u32, u32, [u8; 3])]) -> Result<DataHandle<MyCell>> {
+    let size = 12;
+    let mut image = RgbImage::from_pixel(size, size, Rgb([255, 255, 255]));
+    for index in 0..size {
+        for edge in [0, size - 1] {
+            image.put_pixel(index, edge, Rgb([0, 0, 0]));
+            image.put_pixel(edge, index, Rgb([0, 0, 0]));
+        }
+    }
+    for (x_pos, y_pos, colour) in markers {
+        image.put_pixel(*x_pos, *y_pos, Rgb(*colour));
+    }
+    backend::from_image(
+        String::from("room"),
+        DynamicImage::ImageRgb8(image),
+        &Terrain::Uniform,
+    )
+}
+
+#[test]
+fn missing_files_are_reported_by_path() {
+    let path = Path::new("./resources/images/missing.jpg");
+    match backend::from_path::<MyCell>(path, &Terrain::Uniform) {
+        Err(Error::FileNotFound(name)) => assert!(name.ends_with("missing.jpg")),
+        other => panic!("expected FileNotFound, got {:?}", other.err()),
+    }
+}
+
+#[test]
+fn missing_markers_are_reported() {
+    let no_start = maze(&[(8, 8, [0, 0, 255])]).unwrap();
+    assert!(matches!(no_start.get_start(), Err(Error::NoStartMarker)));
+    assert!(no_start.get_end().is_ok());
+
+    let no_end = maze(&[(3, 3, [255, 0, 0])]).unwrap();
+    assert!(no_end.get_start().is_ok());
+    assert!(matches!(no_end.get_end(), Err(Error::NoEndMarker)));
+
+    let blank = DynamicImage::ImageRgb8(RgbImage::from_pixel(8, 8, Rgb([255, 255, 255])));
+    assert!(matches!(
+        backend::from_image::<MyCell>(String::from("blank"), blank, &Terrain::Uniform),
+        Err(Error::MazeBoundsNotFound)
+    ));
+}
+
+#[test]
+fn positions_outside_the_maze_are_rejected() {
+    let index = maze(&[(3, 3, [255, 0, 0]), (8, 8, [0, 0, 255])]).unwrap();
+    let start = index.get_start().unwrap();
+    let end = index.get_end().unwrap();
+    let outside = Position::new(40, 2);
+    for algorithm in [Algorithm::AStar, Algorithm::Jps, Algorithm::Bidirectional] {
+        let options = SearchOptions {
+            algorithm,
+            ..SearchOptions::default()
+        };
+        assert!(matches!(
+            find_shortest(index.matrix(), &outside, &end, &options),
+            Err(Error::OutOfBounds(position)) if position == outside
+        ));
+        assert!(matches!(
+            find_shortest(index.matrix(), &start, &outside, &options),
+            Err(Error::OutOfBounds(position)) if position == outside
+        ));
+    }
+    assert!(matches!(
+        index.to_matrix(100, 3),
+        Err(Error::OutOfBounds(_))
+    ));
+}
@@ -0,0 +1,95 @@
+use std::path::Path;
+
+use djikstra::backend::{self, export};
+use djikstra::{find_shortest, Cell, DataHandle, MyCell, Position, SearchOptions, Terrain};
+
+fn load(name: &str) -> DataHandle<MyCell> {
+    let path = format!("./resources/images/{}.jpg", name);
+    backend::from_path::<MyCell>(Path::new(&path), &Terrain::Uniform).unwrap()
+}
+
+#[test]
+fn exports_list_the_path_in_matrix_and_image_coordinates() {
+    let index = load("squaremed7");
+    let start = index.get_start().unwrap();
+    let end = index.get_end().unwrap();
+    let result = find_shortest(index.matrix(), &start, &end, &SearchOptions::default()).unwrap();
+    let path = result.path.as_ref().unwrap();
+
+    let csv = index.path_csv(&result);
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some(export::PATH_HEADER));
+    let rows: Vec<Vec<u32>> = lines
+        .map(|line| {
+            line.split(',')
+                .map(|field| field.parse().unwrap())
+                .collect()
+        })
+        .collect();
+    assert_eq!(rows.len(), path.len());
+    for (step, (row, position)) in rows.iter().zip(path).enumerate() {
+        assert_eq!(row[0] as usize, step);
+        assert_eq!(Position::new(row[2], row[1]), *position);
+        assert_eq!(index.to_matrix(row[3], row[4]).unwrap(), *position);
+    }
+
+    let summary = index.summary_csv(&result, &start, &end);
+    let mut lines = summary.lines();
+    assert_eq!(lines.next(), Some(export::SUMMARY_HEADER));
+    let fields: Vec<&str> = lines.next().unwrap().split(',').collect();
+    assert_eq!(fields.len(), export::SUMMARY_HEADER.split(',').count());
+    assert_eq!(fields[0], "squaremed7");
+    assert_eq!(fields[1], "true");
+    let (image_x, image_y) = index.to_image(&start);
+    assert_eq!(fields[6..8], [image_x.to_string(), image_y.to_string()]);
+    assert_eq!(fields[11], result.stats.path_cost.to_string());
+    let reopenings = export::SUMMARY_HEADER
+        .split(',')
+        .position(|field| field == "reopenings")
+        .unwrap();
+    assert_eq!(fields[reopenings], "");
+
+    let json = index.to_json(&result, &start, &end);
+    assert!(json.contains("\"maze\": \"squaremed7\""));
+    assert!(json.contains("\"found\": true"));
+    assert!(json.contains(&format!(
+        "\"start\": {{ \"matrix\": [{}, {}], \"image\": [{}, {}] }}",
+        start.x(),
+        start.y(),
+        image_x,
+        image_y
+    )));
+    assert!(json.contains(&format!("\"cost\": {}", result.stats.path_cost)));
+    assert!(json.contains(&format!(
+        "\"nodes_expanded\": {}",
+        result.stats.nodes_expanded
+    )));
+    assert!(json.contains("\"waypoints\": null"));
+    assert!(json.contains("\"reopenings\": null"));
+    assert_eq!(json.matches('[').count(), 4 + 2 * (path.len() + 1));
+}
+
+#[test]
+fn exports_without_a_path_keep_the_ends() {
+    let index = load("squareeasy1");
+    let start = index.get_start().unwrap();
+    // A free corner walled off from the maze.
+    let cut_off = Position::new(0, 0);
+    assert!(!index.matrix()[&cut_off].is_wall());
+    let result =
+        find_shortest(index.matrix(), &start, &cut_off, &SearchOptions::default()).unwrap();
+    assert!(result.path.is_none());
+
+    assert_eq!(
+        index.path_csv(&result),
+        format!("{}\n", export::PATH_HEADER)
+    );
+    let summary = index.summary_csv(&result, &start, &cut_off);
+    let fields: Vec<&str> = summary.lines().nth(1).unwrap().split(',').collect();
+    assert_eq!(fields[..2], ["squareeasy1", "false"]);
+    assert_eq!(fields[11], "");
+    let json = index.to_json(&result, &start, &cut_off);
+    assert!(json.contains("\"found\": false"));
+    assert!(json.contains("\"cost\": null"));
+    assert!(json.contains("\"path\": null"));
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use djikstra::djikstra::heuristic::from_name;
+use djikstra::{find_shortest, Connectivity, Error, Position, SearchOptions};
+
+use common::{fresh_cost, room};
+
+const NAMES: [&str; 5] = ["manhattan", "euclidean", "octile", "chebyshev", "zero"];
+
+#[test]
+fn admissible_heuristics_never_overestimate() {
+    let matrix = room(20);
+    let start = Position::new(2, 2);
+    for connectivity in [Connectivity::Four, Connectivity::Eight] {
+        let options = SearchOptions {
+            connectivity,
+            ..SearchOptions::default()
+        };
+        for name in NAMES {
+            let heuristic = from_name(name).unwrap();
+            if !heuristic.is_admissible(&connectivity) {
+                continue;
+            }
+            for goal in [(2, 17), (17, 17), (12, 5), (3, 16)] {
+                let goal = Position::new(goal.0, goal.1);
+                let cost = find_shortest(&matrix, &start, &goal, &options)
+                    .unwrap()
+                    .stats
+                    .path_cost;
+                assert!(heuristic.estimate(&start, &goal) <= cost, "{}", name);
+            }
+        }
+    }
+}
+
+#[test]
+fn octile_overestimates_unit_diagonals() {
+    let matrix = room(20);
+    let (start, goal) = (Position::new(2, 2), Position::new(12, 12));
+    // Ten diagonal steps that cost 1 each.
+    assert_eq!(fresh_cost(&matrix, &start, &goal), Some(10));
+    let octile = from_name("octile").unwrap();
+    assert_eq!(octile.estimate(&start, &goal), 14);
+    assert!(!octile.is_admissible(&Connectivity::Eight));
+    assert!(octile.is_admissible(&Connectivity::Four));
+
+    for name in ["manhattan", "euclidean", "octile"] {
+        let options = SearchOptions {
+            heuristic: from_name(name).unwrap(),
+            ..SearchOptions::default()
+        };
+        assert!(matches!(
+            find_shortest(&matrix, &start, &goal, &options),
+            Err(Error::InvalidOptions(_))
+        ));
+    }
+}
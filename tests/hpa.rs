@@ -0,0 +1,160 @@
+mod common;
+
+use std::path::Path;
+
+use djikstra::backend;
+use djikstra::djikstra::{heuristic::Chebyshev, hpa::DEFAULT_CLUSTER_SIZE};
+use djikstra::{
+    Cell, CellUpdate, Connectivity, Hierarchy, MyCell, Position, SearchResult, Terrain,
+};
+
+use common::{assert_walkable, fresh_cost, room};
+
+#[test]
+fn hpa_finds_near_shortest_paths_and_rebuilds_only_edited_clusters() {
+    let matrix = room(60);
+    let start = Position::new(5, 3);
+    let goal = Position::new(52, 54);
+    let rows = matrix.y_size() as u32;
+    let mut hierarchy = Hierarchy::new(matrix, 10, Connectivity::Eight).unwrap();
+    assert!(hierarchy.node_count() > 0);
+
+    let open = hierarchy
+        .find_path(&start, &goal, &Chebyshev, None)
+        .unwrap();
+    assert_walkable(hierarchy.matrix(), open.path.as_ref().unwrap());
+    assert_eq!(open.path.as_ref().unwrap()[0], start);
+    let optimal = fresh_cost(hierarchy.matrix(), &start, &goal).unwrap();
+    assert!(open.stats.path_cost >= optimal);
+    assert!(open.stats.path_cost as f64 <= optimal as f64 * 1.2);
+
+    // A wall down the middle of the third column of clusters, with a single
+    // gap. It cuts the borders between those clusters but none of the
+    // borders with their neighbours, so only that column is rebuilt.
+    let wall: Vec<CellUpdate> = (0..rows)
+        .filter(|y_pos| *y_pos != 44)
+        .map(|y_pos| CellUpdate::Wall(Position::new(y_pos, 25)))
+        .collect();
+    assert_eq!(hierarchy.update(&wall).unwrap(), [2, 8, 14, 20, 26, 32]);
+    let walled = hierarchy
+        .find_path(&start, &goal, &Chebyshev, None)
+        .unwrap();
+    assert_walkable(hierarchy.matrix(), walled.path.as_ref().unwrap());
+    assert!(walled
+        .path
+        .as_ref()
+        .unwrap()
+        .contains(&Position::new(44, 25)));
+
+    // Start and end in the same cluster.
+    let near = Position::new(8, 7);
+    let local = hierarchy
+        .find_path(&start, &near, &Chebyshev, None)
+        .unwrap();
+    assert_eq!(local.stats.path_cost, start.distance(&near));
+
+    // Closing the gap inside a cluster leaves every border as it was.
+    assert_eq!(
+        hierarchy
+            .update(&[CellUpdate::Wall(Position::new(44, 25))])
+            .unwrap(),
+        [26]
+    );
+    assert!(hierarchy
+        .find_path(&start, &goal, &Chebyshev, None)
+        .unwrap()
+        .path
+        .is_none());
+
+    // A wall on the edge of a cluster splits the entrance into the one
+    // below, so both are rebuilt, and no other.
+    assert_eq!(
+        hierarchy
+            .update(&[CellUpdate::Wall(Position::new(19, 15))])
+            .unwrap(),
+        [7, 13]
+    );
+    assert_eq!(hierarchy.update(&[]).unwrap(), []);
+
+    // The edited hierarchy answers like one built from scratch.
+    let fresh = Hierarchy::new(hierarchy.matrix().clone(), 10, Connectivity::Eight).unwrap();
+    let left = Position::new(52, 12);
+    assert_eq!(
+        hierarchy
+            .find_path(&start, &left, &Chebyshev, None)
+            .unwrap()
+            .stats
+            .path_cost,
+        fresh
+            .find_path(&start, &left, &Chebyshev, None)
+            .unwrap()
+            .stats
+            .path_cost
+    );
+    assert_eq!(hierarchy.node_count(), fresh.node_count());
+}
+
+#[test]
+fn hpa_stays_close_to_the_shortest_path_on_a_mega_maze() {
+    let index = backend::from_path::<MyCell>(
+        Path::new("./resources/images/tubemega7.jpg"),
+        &Terrain::Uniform,
+    )
+    .unwrap();
+    let start = index.get_start().unwrap();
+    let end = index.get_end().unwrap();
+    let mut hierarchy = Hierarchy::new(
+        index.matrix().clone(),
+        DEFAULT_CLUSTER_SIZE,
+        Connectivity::Eight,
+    )
+    .unwrap();
+    // Paths through the abstract graph bend at cluster borders; on the
+    // bundled mazes that costs well under 5% over the shortest path.
+    let assert_near_shortest = |hierarchy: &Hierarchy<MyCell>, result: &SearchResult| {
+        let optimal = fresh_cost(hierarchy.matrix(), &start, &end).unwrap();
+        let path = result.path.as_ref().unwrap();
+        assert_walkable(hierarchy.matrix(), path);
+        assert_eq!((path[0], path[path.len() - 1]), (start, end));
+        let cost: u32 = path
+            .windows(2)
+            .map(|step| hierarchy.matrix()[&step[1]].get_cost())
+            .sum();
+        assert_eq!(result.stats.path_cost, cost);
+        assert!(cost >= optimal);
+        assert!(cost as f64 <= optimal as f64 * 1.05);
+    };
+
+    let result = hierarchy.find_path(&start, &end, &Chebyshev, None).unwrap();
+    assert_near_shortest(&hierarchy, &result);
+
+    // Wall off a cell half way along the path; the maze has another way
+    // round it.
+    let path = result.path.unwrap();
+    let blocked = path[path.len() / 2];
+    let rebuilt = hierarchy.update(&[CellUpdate::Wall(blocked)]).unwrap();
+    let clusters_x = index.matrix().x_size().div_ceil(DEFAULT_CLUSTER_SIZE);
+    let cluster = blocked.y() as usize / DEFAULT_CLUSTER_SIZE * clusters_x
+        + blocked.x() as usize / DEFAULT_CLUSTER_SIZE;
+    assert!(rebuilt.contains(&cluster));
+    assert!(rebuilt.len() <= 5);
+    let rerouted = hierarchy.find_path(&start, &end, &Chebyshev, None).unwrap();
+    assert_near_shortest(&hierarchy, &rerouted);
+    assert!(!rerouted.path.as_ref().unwrap().contains(&blocked));
+
+    let fresh = Hierarchy::new(
+        hierarchy.matrix().clone(),
+        DEFAULT_CLUSTER_SIZE,
+        Connectivity::Eight,
+    )
+    .unwrap();
+    assert_eq!(hierarchy.node_count(), fresh.node_count());
+    assert_eq!(
+        rerouted.stats.path_cost,
+        fresh
+            .find_path(&start, &end, &Chebyshev, None)
+            .unwrap()
+            .stats
+            .path_cost
+    );
+}
@@ -0,0 +1,33 @@
+mod common;
+
+use djikstra::backend;
+use djikstra::{Algorithm, MyCell, Terrain};
+
+use common::{assert_walkable, bundled_images, cost, solve};
+
+#[test]
+fn jps_matches_astar_on_every_bundled_image() {
+    for path in bundled_images() {
+        let index = backend::from_path::<MyCell>(&path, &Terrain::Uniform).unwrap();
+        let astar = solve(&index, Algorithm::AStar);
+        let jps = solve(&index, Algorithm::Jps);
+
+        let jps_path = jps.path.as_ref().expect("jps found no path");
+        assert_eq!(
+            Some(jps.stats.path_cost),
+            cost(&astar),
+            "{} costs differ",
+            index.name()
+        );
+        assert_eq!(jps_path.first(), Some(&index.get_start().unwrap()));
+        assert_eq!(jps_path.last(), Some(&index.get_end().unwrap()));
+        assert_walkable(index.matrix(), jps_path);
+        assert!(
+            jps.stats.nodes_expanded < astar.stats.nodes_expanded,
+            "{} expanded {} nodes with jps and {} with astar",
+            index.name(),
+            jps.stats.nodes_expanded,
+            astar.stats.nodes_expanded
+        );
+    }
+}
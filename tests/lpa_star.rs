@@ -0,0 +1,50 @@
+mod common;
+
+use djikstra::{CellUpdate, LifelongAStar, Position, SearchOptions};
+
+use common::{cost, fresh_cost, room};
+
+#[test]
+fn lpa_star_reuses_its_distances_after_edits() {
+    let matrix = room(60);
+    let start = Position::new(30, 2);
+    let goal = Position::new(30, 55);
+    let rows = matrix.y_size() as u32;
+    let mut planner =
+        LifelongAStar::new(matrix.clone(), start, goal, SearchOptions::default()).unwrap();
+    let mut expected = matrix;
+
+    let first = planner.plan();
+    let original = fresh_cost(&expected, &start, &goal);
+    assert_eq!(cost(&first), original);
+
+    // A wall with a gap near the goal, and a swamp in front of the gap.
+    let mut edits: Vec<CellUpdate> = (0..rows)
+        .filter(|y_pos| *y_pos != 45)
+        .map(|y_pos| CellUpdate::Wall(Position::new(y_pos, 40)))
+        .collect();
+    for y_pos in 40..50 {
+        edits.push(CellUpdate::Cost(Position::new(y_pos, 38), 5));
+    }
+    for update in &edits {
+        expected.apply(update).unwrap();
+    }
+    planner.update(&edits).unwrap();
+    let edited = planner.plan();
+    assert_eq!(cost(&edited), fresh_cost(&expected, &start, &goal));
+    assert!(edited.stats.nodes_expanded > 0);
+
+    // Nothing changed, so nothing is expanded again.
+    assert_eq!(planner.plan().stats.nodes_expanded, 0);
+
+    // Taking the wall down brings the original cost back.
+    let removed: Vec<CellUpdate> = (0..rows)
+        .map(|y_pos| CellUpdate::Free(Position::new(y_pos, 40)))
+        .collect();
+    planner.update(&removed).unwrap();
+    let restored = planner.plan();
+    assert_eq!(Some(restored.stats.path_cost), original);
+    assert!(restored.stats.nodes_expanded < first.stats.nodes_expanded);
+
+    assert!(planner.update(&[CellUpdate::Cost(start, 0)]).is_err());
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use djikstra::backend;
+use djikstra::{
+    find_shortest, Algorithm, CellUpdate, DStarLite, Expansion, MyCell, Position, SearchOptions,
+    Terrain,
+};
+
+use common::{fresh_cost, room};
+
+/// Options for `algorithm` that record every expansion into the returned
+/// list.
+fn traced(algorithm: Algorithm) -> (SearchOptions, Arc<Mutex<Vec<Expansion>>>) {
+    let trace = Arc::new(Mutex::new(Vec::new()));
+    let recorder = Arc::clone(&trace);
+    let options = SearchOptions {
+        algorithm,
+        weight: 2.5,
+        on_expand: Some(Arc::new(move |expansion: &Expansion| {
+            recorder.lock().unwrap().push(*expansion)
+        })),
+        ..SearchOptions::default()
+    };
+    (options, trace)
+}
+
+#[test]
+fn every_algorithm_reports_each_expansion_to_the_hook() {
+    let mut matrix = room(30);
+    for y_pos in 0..24 {
+        matrix
+            .apply(&CellUpdate::Wall(Position::new(y_pos, 15)))
+            .unwrap();
+    }
+    let start = Position::new(5, 3);
+    let end = Position::new(5, 26);
+    let algorithms = [
+        Algorithm::AStar,
+        Algorithm::Dijkstra,
+        Algorithm::Weighted,
+        Algorithm::Theta,
+        Algorithm::LazyTheta,
+        Algorithm::Ara,
+        Algorithm::Jps,
+        Algorithm::Bidirectional,
+    ];
+    for algorithm in algorithms {
+        let (options, trace) = traced(algorithm);
+        let result = find_shortest(&matrix, &start, &end, &options).unwrap();
+        let trace = trace.lock().unwrap();
+        let stats = &result.stats;
+        assert!(result.path.is_some(), "{:?} found no path", algorithm);
+        assert_eq!(trace.len(), stats.nodes_expanded, "{:?}", algorithm);
+        assert!(
+            trace
+                .iter()
+                .enumerate()
+                .all(|(index, expansion)| expansion.number == index + 1),
+            "{:?} numbers its expansions out of order",
+            algorithm
+        );
+        assert!(
+            stats.nodes_generated >= stats.nodes_expanded,
+            "{:?}",
+            algorithm
+        );
+        assert!(stats.heap_peak > 0, "{:?}", algorithm);
+        assert!(stats.heap_peak <= stats.nodes_generated, "{:?}", algorithm);
+    }
+
+    let (options, trace) = traced(Algorithm::AStar);
+    let result = find_shortest(&matrix, &start, &end, &options).unwrap();
+    assert_eq!(trace.lock().unwrap()[0].position, start);
+    // A* never reopens a node, so it does not report reopenings at all.
+    assert_eq!(result.stats.reopenings, None);
+    assert!(!result.stats.to_string().contains("reopenings"));
+    assert_eq!(
+        Some(result.stats.path_cost),
+        fresh_cost(&matrix, &start, &end)
+    );
+    assert_eq!(
+        result.stats.path_length,
+        result.path.as_ref().unwrap().len()
+    );
+}
+
+#[test]
+fn ara_and_dstar_lite_count_reopenings() {
+    let index = backend::from_path::<MyCell>(
+        std::path::Path::new("./resources/images/squaremed2.jpg"),
+        &Terrain::Uniform,
+    )
+    .unwrap();
+    let start = index.get_start().unwrap();
+    let end = index.get_end().unwrap();
+    let options = SearchOptions {
+        algorithm: Algorithm::Ara,
+        weight: 5.0,
+        ..SearchOptions::default()
+    };
+    let result = find_shortest(index.matrix(), &start, &end, &options).unwrap();
+    let reopenings = result.stats.reopenings.unwrap();
+    assert!(reopenings > 0);
+    assert!(result
+        .stats
+        .to_string()
+        .contains(&format!("{} reopenings", reopenings)));
+    assert_eq!(
+        Some(result.stats.path_cost),
+        fresh_cost(index.matrix(), &start, &end)
+    );
+
+    // Cutting the straight path forces cells behind the wall to be raised
+    // and expanded again.
+    let matrix = room(30);
+    let start = Position::new(15, 2);
+    let goal = Position::new(15, 27);
+    let mut planner = DStarLite::new(matrix, start, goal, SearchOptions::default()).unwrap();
+    assert_eq!(planner.plan().stats.reopenings, Some(0));
+    let wall: Vec<CellUpdate> = (1..27)
+        .map(|y_pos| CellUpdate::Wall(Position::new(y_pos, 14)))
+        .collect();
+    planner.update(&wall).unwrap();
+    let repaired = planner.plan();
+    assert!(repaired.path.is_some());
+    assert!(repaired.stats.reopenings.unwrap() > 0);
+    assert!(repaired.stats.nodes_generated > 0);
+}
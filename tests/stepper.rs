@@ -0,0 +1,127 @@
+mod common;
+
+use std::path::Path;
+
+use image::Rgb;
+
+use djikstra::backend;
+use djikstra::{
+    find_shortest, Algorithm, CellUpdate, Error, MyCell, Position, SearchOptions, Step, Stepper,
+    Terrain,
+};
+
+use common::{fresh_cost, room};
+
+#[test]
+fn stepping_through_a_star_matches_the_full_search() {
+    let mut matrix = room(24);
+    for y_pos in 4..24 {
+        matrix
+            .apply(&CellUpdate::Wall(Position::new(y_pos, 12)))
+            .unwrap();
+    }
+    let start = Position::new(20, 3);
+    let end = Position::new(20, 20);
+    let options = SearchOptions::default();
+    let mut stepper = Stepper::new(&matrix, &start, &end, &options).unwrap();
+
+    let first: Step = stepper.next().unwrap();
+    assert_eq!(first.position, start);
+    assert_eq!(first.distance, 0);
+    assert!(!first.pushed.is_empty());
+    assert_eq!(first.open, first.pushed.len());
+    for position in &first.pushed {
+        assert_eq!(stepper.state().get_parent(position), Some(start));
+    }
+
+    // Pause half way and draw what has been searched so far.
+    let steps: Vec<Step> = stepper.by_ref().take(40).collect();
+    assert_eq!(steps.len(), 40);
+    let last = steps[steps.len() - 1].position;
+    let snapshot = stepper.snapshot();
+    let partial = snapshot.path.unwrap();
+    assert_eq!(partial[0], start);
+    assert_eq!(partial[partial.len() - 1], last);
+    assert!(!stepper.is_finished());
+    assert_eq!(stepper.stats().nodes_expanded, 41);
+
+    let result = stepper.into_result();
+    let full = find_shortest(&matrix, &start, &end, &options).unwrap();
+    assert_eq!(result.path, full.path);
+    assert_eq!(result.stats.nodes_expanded, full.stats.nodes_expanded);
+    assert_eq!(
+        Some(result.stats.path_cost),
+        fresh_cost(&matrix, &start, &end)
+    );
+
+    let count = Stepper::new(&matrix, &start, &end, &options)
+        .unwrap()
+        .count();
+    assert_eq!(count, full.stats.nodes_expanded);
+}
+
+#[test]
+fn only_a_star_and_its_variants_can_be_stepped() {
+    let matrix = room(10);
+    let (start, end) = (Position::new(2, 2), Position::new(7, 7));
+    for algorithm in [Algorithm::Dijkstra, Algorithm::Weighted] {
+        let options = SearchOptions {
+            algorithm,
+            weight: 2.0,
+            ..SearchOptions::default()
+        };
+        let stepper = Stepper::new(&matrix, &start, &end, &options).unwrap();
+        assert!(stepper.into_result().path.is_some());
+    }
+    let options = SearchOptions {
+        algorithm: Algorithm::Theta,
+        ..SearchOptions::default()
+    };
+    assert!(matches!(
+        Stepper::new(&matrix, &start, &end, &options),
+        Err(Error::InvalidOptions(_))
+    ));
+}
+
+#[test]
+fn animating_the_search_ends_on_the_rendered_result() {
+    let index = backend::from_path::<MyCell>(
+        Path::new("./resources/images/squareeasy1.jpg"),
+        &Terrain::Uniform,
+    )
+    .unwrap();
+    let start = index.get_start().unwrap();
+    let end = index.get_end().unwrap();
+    let options = SearchOptions::default();
+    let stepper = Stepper::new(index.matrix(), &start, &end, &options).unwrap();
+    let mut frames = Vec::new();
+    let result = index
+        .render_search(stepper, 5000, |frame| {
+            frames.push(frame);
+            Ok(())
+        })
+        .unwrap();
+
+    let expanded = result.stats.nodes_expanded;
+    assert_eq!(frames.len(), expanded.div_ceil(5000) + 1);
+    assert_eq!(
+        Some(result.stats.path_cost),
+        fresh_cost(index.matrix(), &start, &end)
+    );
+    assert_eq!(frames[frames.len() - 1], index.render(&result));
+
+    // Painting each expansion onto one canvas draws what rendering a
+    // snapshot of the search at that point would.
+    let mut stepper = Stepper::new(index.matrix(), &start, &end, &options).unwrap();
+    for (number, frame) in frames[..frames.len() - 1].iter().enumerate() {
+        let steps = if number == 0 { 1 } else { 5000 };
+        assert_eq!(stepper.by_ref().take(steps).count(), steps);
+        let mut expected = index.render(&stepper.snapshot());
+        for each in stepper.state().frontier() {
+            let (x_pos, y_pos) = index.to_image(&each);
+            expected.put_pixel(x_pos, y_pos, Rgb([255, 165, 0]));
+        }
+        assert_eq!(*frame, expected);
+        assert!(frame.pixels().any(|pixel| pixel.0 == [255, 165, 0]));
+    }
+}
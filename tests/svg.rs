@@ -0,0 +1,87 @@
+use std::path::Path;
+
+use djikstra::backend::{self, svg::SvgOptions};
+use djikstra::{find_shortest, MyCell, Position, SearchOptions, Terrain};
+
+/// The points of the path polyline.
+fn points(svg: &str) -> Vec<(f64, f64)> {
+    let start = svg.find("<polyline id=\"path\" points=\"").unwrap() + 28;
+    let end = start + svg[start..].find('"').unwrap();
+    svg[start..end]
+        .split(' ')
+        .map(|point| {
+            let (x_pos, y_pos) = point.split_once(',').unwrap();
+            (x_pos.parse().unwrap(), y_pos.parse().unwrap())
+        })
+        .collect()
+}
+
+#[test]
+fn svg_draws_the_path_in_source_image_coordinates() {
+    let index = backend::from_path::<MyCell>(
+        Path::new("./resources/images/tubemed12.jpg"),
+        &Terrain::Uniform,
+    )
+    .unwrap();
+    let start = index.get_start().unwrap();
+    let end = index.get_end().unwrap();
+    let result = find_shortest(index.matrix(), &start, &end, &SearchOptions::default()).unwrap();
+    let path = result.path.as_ref().unwrap();
+
+    let svg = index
+        .render_svg(&result, &start, &end, &SvgOptions::default())
+        .unwrap();
+    assert!(svg.starts_with("<svg "));
+    assert!(svg.contains("xlink:href=\"data:image/png;base64,iVBORw0KGgo"));
+    assert!(svg.contains("<circle id=\"start\""));
+    assert!(!svg.contains("<rect"));
+    let points = points(&svg);
+    assert_eq!(points.len(), path.len());
+    for (position, (x_pos, y_pos)) in path.iter().zip(&points) {
+        let (image_x, image_y) = index.to_image(position);
+        assert_eq!(
+            (*x_pos, *y_pos),
+            (image_x as f64 + 0.5, image_y as f64 + 0.5)
+        );
+        assert_eq!(index.to_matrix(image_x, image_y).unwrap(), *position);
+    }
+
+    let options = SvgOptions {
+        image_href: Some(String::from("maze \"1\".jpg")),
+        visited: true,
+        markers: false,
+    };
+    let svg = index.render_svg(&result, &start, &end, &options).unwrap();
+    assert!(svg.contains("xlink:href=\"maze &quot;1&quot;.jpg\""));
+    assert!(svg.contains("<g id=\"visited\""));
+    assert!(svg.contains("<rect"));
+    assert!(!svg.contains("<circle"));
+}
+
+#[test]
+fn svg_marks_the_ends_even_without_a_path() {
+    let index = backend::from_path::<MyCell>(
+        Path::new("./resources/images/squareeasy1.jpg"),
+        &Terrain::Uniform,
+    )
+    .unwrap();
+    let start = index.get_start().unwrap();
+    // A free corner walled off from the maze.
+    let end = Position::new(0, 0);
+    let result = find_shortest(index.matrix(), &start, &end, &SearchOptions::default()).unwrap();
+    assert!(result.path.is_none());
+
+    let svg = index
+        .render_svg(&result, &start, &end, &SvgOptions::default())
+        .unwrap();
+    assert!(!svg.contains("<polyline"));
+    for (position, id) in [(start, "start"), (end, "end")] {
+        let (x_pos, y_pos) = index.to_image(&position);
+        assert!(svg.contains(&format!(
+            "<circle id=\"{}\" cx=\"{}\" cy=\"{}\"",
+            id,
+            x_pos as f64 + 0.5,
+            y_pos as f64 + 0.5
+        )));
+    }
+}
@@ -0,0 +1,91 @@
+use image::{DynamicImage, Rgb, RgbImage};
+
+use djikstra::backend::{
+    self,
+    terrain::{MAX_GREY_COST, WALL_GREY},
+};
+use djikstra::{find_shortest, Cell, Error, MyCell, Position, SearchOptions, Terrain};
+
+#[test]
+fn grey_costs_span_the_walkable_greys() {
+    let grey = |level: u8| Terrain::Grey.cost(&[level, level, level]);
+    assert_eq!(grey(255), 1);
+    assert_eq!(grey(WALL_GREY + 1), MAX_GREY_COST);
+    let mut last = 0;
+    for level in (WALL_GREY + 1..=255).rev() {
+        assert!(grey(level) >= last);
+        last = grey(level);
+    }
+    // Colours darker than the walls are not walls, and cost the most.
+    assert_eq!(Terrain::Grey.cost(&[0, 0, 120]), MAX_GREY_COST);
+    assert_eq!(Terrain::Uniform.cost(&[200, 200, 200]), 1);
+}
+
+#[test]
+fn palettes_charge_the_closest_colour() {
+    let terrain: Terrain = "palette:ffffff=1,#00a000=4,8080ff=0".parse().unwrap();
+    assert_eq!(terrain.cost(&[250, 250, 245]), 1);
+    assert_eq!(terrain.cost(&[10, 140, 20]), 4);
+    // Costs never drop below 1.
+    assert_eq!(terrain.cost(&[120, 120, 250]), 1);
+
+    for spec in [
+        "palette:",
+        "palette:ffffff",
+        "palette:fffff=1",
+        "palette:gggggg=1",
+        "palette:ffffff=-1",
+        "palette:ffffff=1,",
+        "sand",
+    ] {
+        assert!(
+            matches!(spec.parse::<Terrain>(), Err(Error::InvalidOptions(_))),
+            "{}",
+            spec
+        );
+    }
+}
+
+#[test]
+fn searches_go_around_expensive_terrain() {
+    // A room with a strip of light grey across the straight way from the
+    // left to the right side, open at the bottom.
+    let size = 30;
+    let mut image = RgbImage::from_pixel(size, size, Rgb([255, 255, 255]));
+    for index in 0..size {
+        for edge in [0, size - 1] {
+            image.put_pixel(index, edge, Rgb([0, 0, 0]));
+            image.put_pixel(edge, index, Rgb([0, 0, 0]));
+        }
+    }
+    for y_pos in 1..size - 6 {
+        for x_pos in 14..17 {
+            image.put_pixel(x_pos, y_pos, Rgb([170, 170, 170]));
+        }
+    }
+    let load = |terrain: &Terrain| {
+        backend::from_image::<MyCell>(
+            String::from("strip"),
+            DynamicImage::ImageRgb8(image.clone()),
+            terrain,
+        )
+        .unwrap()
+    };
+    let start = Position::new(5, 3);
+    let end = Position::new(5, 25);
+    let options = SearchOptions::default();
+
+    let uniform = load(&Terrain::Uniform);
+    let straight = find_shortest(uniform.matrix(), &start, &end, &options).unwrap();
+    assert_eq!(straight.stats.path_cost, start.distance(&end));
+
+    let grey = load(&Terrain::Grey);
+    let strip = Position::new(5, 15);
+    assert!(grey.matrix()[&strip].get_cost() > 1);
+    let detour = find_shortest(grey.matrix(), &start, &end, &options).unwrap();
+    let path = detour.path.unwrap();
+    assert!(path
+        .iter()
+        .all(|position| grey.matrix()[position].get_cost() == 1));
+    assert!(path.len() > straight.path.unwrap().len());
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use std::path::Path;
+
+use djikstra::backend;
+use djikstra::{Algorithm, Connectivity, MyCell, Terrain};
+
+use common::solve;
+
+/// Bundled images with long diagonal runs, small enough to search three
+/// times over in a test.
+const IMAGES: [&str; 4] = [
+    "img.png",
+    "squaremed7.jpg",
+    "tubehard24.jpg",
+    "tubemed12.jpg",
+];
+
+#[test]
+fn theta_cuts_the_corners_of_the_astar_path() {
+    for image in IMAGES {
+        let path = format!("./resources/images/{}", image);
+        let index = backend::from_path::<MyCell>(Path::new(&path), &Terrain::Uniform).unwrap();
+        let name = index.name();
+        let astar = solve(&index, Algorithm::AStar);
+        let theta = solve(&index, Algorithm::Theta);
+        let lazy = solve(&index, Algorithm::LazyTheta);
+
+        for result in [&theta, &lazy] {
+            let waypoints = result.waypoints.as_ref().expect("no waypoints");
+            assert_eq!(waypoints.first(), Some(&index.get_start().unwrap()));
+            assert_eq!(waypoints.last(), Some(&index.get_end().unwrap()));
+            for leg in waypoints.windows(2) {
+                assert!(
+                    index
+                        .matrix()
+                        .line_of_sight(&leg[0], &leg[1], &Connectivity::Eight),
+                    "{} has a leg through a wall from {:?} to {:?}",
+                    name,
+                    leg[0],
+                    leg[1]
+                );
+            }
+            assert!(
+                result.stats.euclidean_length <= astar.stats.euclidean_length + 1e-9,
+                "{}: {} against {} for astar",
+                name,
+                result.stats.euclidean_length,
+                astar.stats.euclidean_length
+            );
+        }
+        // Lazy Theta* only checks the parents of the cells it expands, which
+        // costs it a little length and saves most of the checks.
+        assert!(
+            lazy.stats.euclidean_length >= theta.stats.euclidean_length - 1e-9,
+            "{}",
+            name
+        );
+        assert!(
+            lazy.stats.sight_checks < theta.stats.sight_checks,
+            "{}",
+            name
+        );
+        assert_eq!(astar.stats.sight_checks, 0);
+    }
+}
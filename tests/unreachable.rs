@@ -0,0 +1,152 @@
+mod common;
+
+use djikstra::backend::matrix::Matrix;
+use djikstra::djikstra::alternatives::{diverse_routes, k_shortest};
+use djikstra::djikstra::cbs::{find_plan, Agent, DEFAULT_MAX_NODES};
+use djikstra::djikstra::waypoints::find_nearest;
+use djikstra::{
+    find_shortest, Algorithm, Cell, CellUpdate, Connectivity, Error, MyCell, Position,
+    SearchOptions, Stepper,
+};
+
+use common::{fresh_cost, options, room};
+
+const ALGORITHMS: [Algorithm; 8] = [
+    Algorithm::AStar,
+    Algorithm::Dijkstra,
+    Algorithm::Weighted,
+    Algorithm::Ara,
+    Algorithm::Jps,
+    Algorithm::Bidirectional,
+    Algorithm::Theta,
+    Algorithm::LazyTheta,
+];
+
+fn weighted(algorithm: Algorithm) -> SearchOptions {
+    SearchOptions {
+        weight: 2.0,
+        ..options(algorithm)
+    }
+}
+
+#[test]
+fn unreachable_ends_give_no_path_and_the_explored_region() {
+    // A wall splits the room in two; the start is on the left.
+    let mut matrix = room(20);
+    for y_pos in 0..20 {
+        matrix
+            .apply(&CellUpdate::Wall(Position::new(y_pos, 8)))
+            .unwrap();
+    }
+    let start = Position::new(10, 3);
+    let end = Position::new(10, 15);
+    assert_eq!(fresh_cost(&matrix, &start, &end), None);
+
+    for algorithm in ALGORITHMS {
+        let result = find_shortest(&matrix, &start, &end, &weighted(algorithm)).unwrap();
+        assert!(result.path.is_none(), "{:?}", algorithm);
+        assert_eq!(result.stats.path_cost, 0, "{:?}", algorithm);
+        assert!(result.stats.nodes_expanded > 0, "{:?}", algorithm);
+        // Nothing behind the wall was explored. Searches that visit every
+        // cell they reach explored all of the start's side; jump point
+        // search skips cells, and bidirectional search stops once either
+        // side runs out.
+        let exhaustive = !matches!(algorithm, Algorithm::Jps | Algorithm::Bidirectional);
+        for y_pos in 1..19 {
+            for x_pos in 1..19 {
+                let position = Position::new(y_pos, x_pos);
+                let free = !matrix[&position].is_wall();
+                if free && x_pos < 8 && exhaustive {
+                    assert!(result.state.is_visited(&position), "{:?}", algorithm);
+                }
+                if x_pos > 8 {
+                    assert!(!result.state.is_visited(&position), "{:?}", algorithm);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn ends_on_a_wall_are_rejected_before_searching() {
+    let (matrix, wall, free) = walled();
+
+    for algorithm in ALGORITHMS {
+        for (start, end) in [(wall, free), (free, wall)] {
+            assert!(
+                matches!(
+                    find_shortest(&matrix, &start, &end, &weighted(algorithm)),
+                    Err(Error::NoPath)
+                ),
+                "{:?}",
+                algorithm
+            );
+        }
+    }
+    let options = SearchOptions::default();
+    assert!(matches!(
+        Stepper::new(&matrix, &free, &wall, &options),
+        Err(Error::NoPath)
+    ));
+}
+
+/// A room with a wall cell in the middle, the wall and a free cell.
+fn walled() -> (Matrix<MyCell>, Position, Position) {
+    let mut matrix = room(12);
+    let wall = Position::new(6, 6);
+    matrix.apply(&CellUpdate::Wall(wall)).unwrap();
+    (matrix, wall, Position::new(2, 2))
+}
+
+#[test]
+fn nearest_goal_rejects_a_wall_start_or_goal() {
+    let (matrix, wall, free) = walled();
+    let options = SearchOptions::default();
+    let other = Position::new(9, 9);
+    for (start, goals) in [(wall, [free, other]), (free, [other, wall])] {
+        assert!(matches!(
+            find_nearest(&matrix, &start, &goals, &options),
+            Err(Error::NoPath)
+        ));
+    }
+}
+
+#[test]
+fn k_shortest_rejects_a_wall_start_or_end() {
+    let (matrix, wall, free) = walled();
+    let options = SearchOptions::default();
+    for (start, end) in [(wall, free), (free, wall)] {
+        assert!(matches!(
+            k_shortest(&matrix, &start, &end, &options, 2),
+            Err(Error::NoPath)
+        ));
+    }
+}
+
+#[test]
+fn diverse_routes_reject_a_wall_start_or_end() {
+    let (matrix, wall, free) = walled();
+    let options = SearchOptions::default();
+    for (start, end) in [(wall, free), (free, wall)] {
+        assert!(matches!(
+            diverse_routes(&matrix, &start, &end, &options, 2, 1.0),
+            Err(Error::NoPath)
+        ));
+    }
+}
+
+#[test]
+fn plans_reject_an_agent_on_a_wall() {
+    let (matrix, wall, free) = walled();
+    let other = Agent {
+        start: Position::new(9, 2),
+        goal: Position::new(2, 9),
+    };
+    for (start, goal) in [(wall, free), (free, wall)] {
+        let agents = [other, Agent { start, goal }];
+        assert!(matches!(
+            find_plan(&matrix, &agents, &Connectivity::Eight, DEFAULT_MAX_NODES),
+            Err(Error::NoPath)
+        ));
+    }
+}
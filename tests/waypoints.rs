@@ -0,0 +1,197 @@
+mod common;
+
+use image::{DynamicImage, Rgb, RgbImage};
+
+use djikstra::backend;
+use djikstra::djikstra::waypoints::{find_nearest, find_route, Order};
+use djikstra::{Cell, CellUpdate, MyCell, Position, SearchOptions, Terrain};
+
+use common::{fresh_cost, room};
+
+#[test]
+fn nearest_goal_is_the_cheapest_to_reach() {
+    let matrix = room(40);
+    let start = Position::new(5, 5);
+    let goals = [
+        Position::new(35, 35),
+        Position::new(5, 20),
+        Position::new(30, 2),
+    ];
+
+    let result = find_nearest(&matrix, &start, &goals, &SearchOptions::default()).unwrap();
+    let path = result.path.unwrap();
+    assert_eq!(path.last(), Some(&goals[1]));
+    assert_eq!(
+        Some(result.stats.path_cost),
+        fresh_cost(&matrix, &start, &goals[1])
+    );
+}
+
+#[test]
+fn optimal_order_is_never_worse_than_the_given_one() {
+    let mut matrix = room(40);
+    let start = Position::new(2, 2);
+    let end = Position::new(2, 35);
+    // Given order zig-zags between the far corner and the near edge.
+    let stops = [
+        Position::new(35, 35),
+        Position::new(5, 10),
+        Position::new(35, 2),
+        Position::new(5, 30),
+    ];
+    let options = SearchOptions::default();
+    // Legs searched the other way round enter this stop instead of leaving it.
+    matrix.apply(&CellUpdate::Cost(stops[1], 5)).unwrap();
+
+    let given = find_route(&matrix, &start, &stops, &end, &options, Order::Given).unwrap();
+    let mut expected = 0;
+    let mut points = vec![start];
+    points.extend_from_slice(&stops);
+    points.push(end);
+    for pair in points.windows(2) {
+        expected += fresh_cost(&matrix, &pair[0], &pair[1]).unwrap();
+    }
+    assert_eq!(given.stats.path_cost, expected);
+    assert_eq!(given.waypoints.as_ref(), Some(&points));
+
+    let optimal = find_route(&matrix, &start, &stops, &end, &options, Order::Optimal).unwrap();
+    assert!(optimal.stats.path_cost < given.stats.path_cost);
+    let visits = optimal.waypoints.unwrap();
+    let expected: u32 = visits
+        .windows(2)
+        .map(|pair| fresh_cost(&matrix, &pair[0], &pair[1]).unwrap())
+        .sum();
+    assert_eq!(optimal.stats.path_cost, expected);
+    let path = optimal.path.unwrap();
+    assert_eq!(path.first(), Some(&start));
+    assert_eq!(path.last(), Some(&end));
+    for stop in &stops {
+        assert!(path.contains(stop));
+    }
+    for step in path.windows(2) {
+        assert_eq!(step[0].distance(&step[1]), 1);
+    }
+}
+
+#[test]
+fn markers_are_found_one_per_patch() {
+    let mut image = RgbImage::from_pixel(30, 30, Rgb([255, 255, 255]));
+    for index in 0..30 {
+        for pixel in [(index, 0), (index, 29), (0, index), (29, index)] {
+            image.put_pixel(pixel.0, pixel.1, Rgb([0, 0, 0]));
+        }
+    }
+    image.put_pixel(3, 3, Rgb([255, 0, 0]));
+    for (x_pos, y_pos) in [(20, 5), (21, 5), (20, 6), (4, 25)] {
+        image.put_pixel(x_pos, y_pos, Rgb([0, 0, 255]));
+    }
+    for (x_pos, y_pos) in [(10, 10), (11, 11), (15, 8), (5, 20)] {
+        image.put_pixel(x_pos, y_pos, Rgb([0, 255, 0]));
+    }
+    let index = backend::from_image::<MyCell>(
+        String::from("markers"),
+        DynamicImage::ImageRgb8(image),
+        &Terrain::Uniform,
+    )
+    .unwrap();
+
+    assert_eq!(index.get_ends().unwrap().len(), 2);
+    let waypoints = index.get_waypoints();
+    assert_eq!(
+        waypoints
+            .iter()
+            .map(|waypoint| index.to_image(waypoint))
+            .collect::<Vec<_>>(),
+        vec![(15, 8), (10, 10), (5, 20)]
+    );
+    for waypoint in &waypoints {
+        assert!(!index.matrix()[waypoint].is_wall());
+    }
+}
+
+#[test]
+fn given_order_is_kept_when_it_differs_from_the_markers() {
+    let mut image = RgbImage::from_pixel(30, 30, Rgb([255, 255, 255]));
+    for index in 0..30 {
+        for pixel in [(index, 0), (index, 29), (0, index), (29, index)] {
+            image.put_pixel(pixel.0, pixel.1, Rgb([0, 0, 0]));
+        }
+    }
+    for (x_pos, y_pos) in [(24, 4), (5, 12), (20, 24)] {
+        image.put_pixel(x_pos, y_pos, Rgb([0, 255, 0]));
+    }
+    let index = backend::from_image::<MyCell>(
+        String::from("stops"),
+        DynamicImage::ImageRgb8(image),
+        &Terrain::Uniform,
+    )
+    .unwrap();
+    let start = index.to_matrix(3, 3).unwrap();
+    let end = index.to_matrix(26, 26).unwrap();
+    let mut stops = index.get_waypoints();
+    stops.reverse();
+    assert_eq!(index.to_image(&stops[0]), (20, 24));
+
+    let options = SearchOptions::default();
+    let route = find_route(index.matrix(), &start, &stops, &end, &options, Order::Given).unwrap();
+    let mut points = vec![start];
+    points.extend_from_slice(&stops);
+    points.push(end);
+    assert_eq!(route.waypoints.as_ref(), Some(&points));
+    let path = route.path.unwrap();
+    let visits: Vec<usize> = stops
+        .iter()
+        .map(|stop| path.iter().position(|each| each == stop).unwrap())
+        .collect();
+    assert!(visits.windows(2).all(|pair| pair[0] < pair[1]));
+    let optimal = find_route(
+        index.matrix(),
+        &start,
+        &stops,
+        &end,
+        &options,
+        Order::Optimal,
+    )
+    .unwrap();
+    assert!(optimal.stats.path_cost < route.stats.path_cost);
+}
+
+#[test]
+fn only_marker_cells_escape_the_walls() {
+    let mut image = RgbImage::from_pixel(30, 30, Rgb([255, 255, 255]));
+    for index in 0..30 {
+        for pixel in [(index, 0), (index, 29), (0, index), (29, index)] {
+            image.put_pixel(pixel.0, pixel.1, Rgb([0, 0, 0]));
+        }
+    }
+    image.put_pixel(3, 3, Rgb([255, 0, 0]));
+    for y_pos in 10..13 {
+        for x_pos in 10..13 {
+            image.put_pixel(x_pos, y_pos, Rgb([0, 255, 0]));
+            image.put_pixel(x_pos + 8, y_pos, Rgb([0, 0, 255]));
+        }
+    }
+    for (x_pos, colour) in [(5, [0, 0, 0]), (6, [120, 120, 120]), (7, [30, 10, 5])] {
+        image.put_pixel(x_pos, 20, Rgb(colour));
+    }
+    let index = backend::from_image::<MyCell>(
+        String::from("colours"),
+        DynamicImage::ImageRgb8(image),
+        &Terrain::Uniform,
+    )
+    .unwrap();
+    let cell = |x_pos, y_pos| index.matrix()[&index.to_matrix(x_pos, y_pos).unwrap()].clone();
+
+    // Whole patches of pure green and blue stay free, centres included.
+    for y_pos in 10..13 {
+        for x_pos in 10..13 {
+            assert!(!cell(x_pos, y_pos).is_wall());
+            assert!(!cell(x_pos + 8, y_pos).is_wall());
+        }
+    }
+    // Black, grey and dark colours are still walls.
+    for x_pos in 5..8 {
+        assert!(cell(x_pos, 20).is_wall());
+    }
+    assert!(!cell(8, 20).is_wall());
+}